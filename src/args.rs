@@ -0,0 +1,56 @@
+use std::{env, path::PathBuf};
+
+/// Options passed to the game on the command line.
+#[derive(Default)]
+pub struct Args {
+    pub seed: Option<u64>,
+    pub save_dir: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub full_speed: bool,
+    pub events: Option<PathBuf>,
+    pub mods: Option<PathBuf>,
+    pub map: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("`--seed` requires a value")?;
+                    let seed = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("`{value}` is not a valid seed"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--save-dir" => {
+                    let value = args.next().ok_or("`--save-dir` requires a value")?;
+                    parsed.save_dir = Some(PathBuf::from(value));
+                }
+                "--replay" => {
+                    let value = args.next().ok_or("`--replay` requires a value")?;
+                    parsed.replay = Some(PathBuf::from(value));
+                }
+                "--full-speed" => parsed.full_speed = true,
+                "--events" => {
+                    let value = args.next().ok_or("`--events` requires a value")?;
+                    parsed.events = Some(PathBuf::from(value));
+                }
+                "--mods" => {
+                    let value = args.next().ok_or("`--mods` requires a value")?;
+                    parsed.mods = Some(PathBuf::from(value));
+                }
+                "--map" => {
+                    let value = args.next().ok_or("`--map` requires a value")?;
+                    parsed.map = Some(PathBuf::from(value));
+                }
+                _ => return Err(format!("Unknown argument `{arg}`")),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,115 @@
+use std::{env, fs::create_dir_all, path::PathBuf, process::exit};
+
+use zombie_apocalypse::{
+    content::Content,
+    rng,
+    simulation::{Config, Report},
+    strategy,
+};
+
+/// Options passed to the simulator on the command line.
+struct Args {
+    config: Config,
+    strategy: String,
+    csv: Option<PathBuf>,
+    events: Option<PathBuf>,
+    mods: Option<PathBuf>,
+    map: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut parsed = Self {
+            config: Config {
+                games: 1000,
+                seed: rng::random_seed(),
+                max_turns: 2000,
+            },
+            strategy: "random".to_owned(),
+            csv: None,
+            events: None,
+            mods: None,
+            map: None,
+        };
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("`{arg}` requires a value"));
+            match arg.as_str() {
+                "--games" => {
+                    let value = value()?;
+                    parsed.config.games = value
+                        .parse()
+                        .map_err(|_| format!("`{value}` is not a valid number of games"))?;
+                }
+                "--seed" => {
+                    let value = value()?;
+                    parsed.config.seed = value
+                        .parse()
+                        .map_err(|_| format!("`{value}` is not a valid seed"))?;
+                }
+                "--max-turns" => {
+                    let value = value()?;
+                    parsed.config.max_turns = value
+                        .parse()
+                        .map_err(|_| format!("`{value}` is not a valid number of turns"))?;
+                }
+                "--strategy" => {
+                    let value = value()?;
+                    if !strategy::NAMES.contains(&value.as_str()) {
+                        return Err(format!(
+                            "Unknown strategy `{value}`, expected one of: {}",
+                            strategy::NAMES.join(", ")
+                        ));
+                    }
+                    parsed.strategy = value;
+                }
+                "--csv" => parsed.csv = Some(PathBuf::from(value()?)),
+                "--events" => parsed.events = Some(PathBuf::from(value()?)),
+                "--mods" => parsed.mods = Some(PathBuf::from(value()?)),
+                "--map" => parsed.map = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("Unknown argument `{arg}`")),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn main() {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!("Usage: simulate [--games <n>] [--seed <u64>] [--max-turns <n>] [--strategy <name>] [--csv <dir>] [--events <file>] [--mods <dir>] [--map <file>]");
+            exit(1);
+        }
+    };
+
+    let content = match Content::load(
+        args.events.as_deref(),
+        args.mods.as_deref(),
+        args.map.as_deref(),
+    ) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("{error}");
+            exit(1);
+        }
+    };
+
+    eprintln!(
+        "Simulating {} games from seed {}...",
+        args.config.games, args.config.seed
+    );
+    let report = Report::run(&args.config, &args.strategy, &content);
+    println!("{report}");
+
+    if let Some(dir) = args.csv {
+        if let Err(error) = create_dir_all(&dir).and_then(|_| report.write_csv(&dir)) {
+            eprintln!("Unable to write CSV files to `{}`: {error}", dir.display());
+            exit(1);
+        }
+        println!("CSV files written to {}", dir.display());
+    }
+}
@@ -0,0 +1,171 @@
+use rand::Rng;
+
+use crate::{
+    combat::Surroundings,
+    io::{Frontend, Question, View},
+    map::Map,
+    morale::{REFUSAL_CHANCE, REST_MORALE},
+    party::{Encounter, Party},
+    rng::GameRng,
+    time::Time,
+};
+
+/// How many watches the night is split into.
+const SHIFTS: i32 = 3;
+/// The health a member gets back from sleeping through a quiet watch.
+const CAMP_HEAL: i32 = 1;
+/// The chance of zombies stumbling onto the camp during each watch.
+const AMBUSH_CHANCE: f64 = 0.15;
+/// How much likelier an ambush is next to a point of interest, where more of
+/// the dead linger.
+const LOCATION_AMBUSH_CHANCE: f64 = 0.07;
+/// How much likelier an ambush is with a horde roaming the area.
+const HORDE_AMBUSH_CHANCE: f64 = 0.4;
+/// How much less likely an ambush is with someone keeping the fire low and
+/// the camp hidden.
+const WATCH_FACTOR: f64 = 0.5;
+/// How many more zombies make it into the camp when nobody is on watch to
+/// raise the alarm.
+const SURPRISE_ZOMBIES: i32 = 1;
+
+/// Beds the party down until the morning, with members taking turns keeping
+/// watch. Everyone who sleeps through a quiet watch heals a little, but
+/// zombies may find the camp before dawn. Returns the attack, if they do.
+pub fn make_camp(
+    party: &mut Party,
+    time: &mut Time,
+    map: &mut Map,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Option<Encounter> {
+    let hours = time.hours_until_dawn();
+    io.clear();
+    io.say(&time.to_string());
+    io.say(&format!(
+        "The party sets up camp for the next {hours} hours, split into {SHIFTS} watches."
+    ));
+
+    let mut watches = Vec::new();
+    for shift in 0..SHIFTS as usize {
+        watches.push(choose_watch(party, map, time, shift, io));
+    }
+
+    io.clear();
+    io.write("The party settles down for the night");
+    io.ellipsis();
+    io.say("");
+
+    for (shift, watch) in watches.into_iter().enumerate() {
+        // The first watch picks up the hours that do not split evenly.
+        let shift_hours = hours / SHIFTS + i32::from(shift == 0) * (hours % SHIFTS);
+        time.advance(shift_hours);
+
+        let on_foot = map.on_foot();
+        let tile = map.get_tile_mut(None);
+        let horde = tile.horde;
+        let mut chance = AMBUSH_CHANCE;
+        if tile.location_type.is_some() {
+            chance += LOCATION_AMBUSH_CHANCE;
+        }
+        if horde > 0 {
+            chance += HORDE_AMBUSH_CHANCE;
+        }
+        let mut watcher = watch.map(|index| party.members[index].name.clone());
+        if let Some(index) = watch {
+            let member = &party.members[index];
+            if member.shaken() && rng.gen_bool(REFUSAL_CHANCE) {
+                io.say(&format!(
+                    "{} refuses to sit up alone in the dark, and goes to sleep.",
+                    member.name
+                ));
+                watcher = None;
+            }
+        }
+        if watcher.is_some() {
+            chance *= WATCH_FACTOR;
+        }
+
+        if rng.gen_bool(chance.min(1.0)) {
+            let mut zombies = rng.gen_range(1..=3) + horde;
+            tile.horde = 0;
+            io.say("");
+            match &watcher {
+                Some(name) => io.say(&format!(
+                    "{name} spots {zombies} shapes shuffling towards the camp, and wakes the others!"
+                )),
+                None => {
+                    zombies += SURPRISE_ZOMBIES;
+                    io.say(&format!(
+                        "With nobody on watch, {zombies} zombies are inside the camp before anyone wakes!"
+                    ));
+                }
+            }
+            io.pause();
+
+            let mut surroundings = Surroundings {
+                time,
+                tile,
+                on_foot,
+            };
+            party.combat(zombies, &mut surroundings, rng, io);
+            party.normalise_values(!on_foot, io);
+            if !party.check_failure() {
+                party.check_infection(&mut surroundings, rng, io);
+                io.say("\nNobody gets any more sleep tonight.");
+            }
+            io.wait();
+            return Some(Encounter::CampAttack);
+        }
+
+        for (index, member) in party.members.iter_mut().enumerate() {
+            if watch != Some(index) {
+                member.heal(CAMP_HEAL);
+            }
+        }
+        match watcher {
+            Some(name) => io.say(&format!("{name}'s watch passes quietly.")),
+            None => io.say("The watch passes quietly."),
+        }
+        io.pause();
+    }
+
+    party.change_morale(REST_MORALE);
+    io.say(&format!(
+        "\nThe party wakes up rested, and packs up the camp.\n{time}"
+    ));
+    io.wait();
+    None
+}
+
+/// Asks who keeps watch during a shift, if anyone.
+fn choose_watch(
+    party: &Party,
+    map: &Map,
+    time: &Time,
+    shift: usize,
+    io: &mut dyn Frontend,
+) -> Option<usize> {
+    io.say(&format!("\nWho keeps watch during watch {}?", shift + 1));
+    for (num, member) in party.members.iter().enumerate() {
+        io.say(&format!(
+            "({num}) {name} ({hp}/{max_hp} HP)",
+            num = num + 1,
+            name = member.name,
+            hp = member.hp,
+            max_hp = member.max_hp,
+        ));
+    }
+    loop {
+        io.say("Enter a number, or `none` to leave the camp unwatched. Whoever keeps watch does not rest.");
+        let input = io.ask(Question::Watch(View::new(party, map, time), shift), ": ");
+        let input = input.trim().to_lowercase();
+        if input == "none" {
+            return None;
+        }
+
+        match input.parse::<usize>() {
+            Ok(choice) if choice > 0 && choice <= party.members.len() => return Some(choice - 1),
+            _ => io.say("Invalid input."),
+        }
+    }
+}
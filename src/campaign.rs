@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{io::Frontend, map::Map, member::Member, party::Party, rng::GameRng};
+
+/// The gang members who can be played, and so may be hiding out somewhere.
+const GANG: &[&str] = &["Velma", "Shaggy", "Fred", "Daphne"];
+/// How close to the party's starting point the gang and the evacuation point
+/// can be.
+pub const MIN_DISTANCE: i32 = 10;
+/// How many landmarks the campaign places: a hideout for each of the gang
+/// but the player, and the evacuation point.
+pub const LANDMARKS: usize = GANG.len();
+/// How much finding one of the gang lifts everyone's spirits.
+const REUNION_MORALE: i32 = 3;
+/// The chance of turning up a clue to where one of the gang is hiding while
+/// searching an ordinary tile.
+pub const CLUE_CHANCE: f64 = 0.2;
+
+/// A special tile placed on the map for the campaign.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Landmark {
+    /// Where one of the gang is holed up, by name.
+    Hideout(String),
+    /// Where the party can get out once the gang is back together.
+    Evacuation,
+}
+
+impl Display for Landmark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Landmark::Hideout(name) => write!(f, "{name}'s hideout"),
+            Landmark::Evacuation => write!(f, "Evacuation point"),
+        }
+    }
+}
+
+/// Hides the rest of the gang around the map and marks the evacuation point,
+/// which the party knows about from the start.
+pub fn start(map: &mut Map, character: &str, rng: &mut GameRng, io: &mut dyn Frontend) {
+    for name in GANG.iter().filter(|name| **name != character) {
+        map.place_landmark(Landmark::Hideout((*name).to_owned()), MIN_DISTANCE, rng);
+    }
+    map.place_landmark(Landmark::Evacuation, MIN_DISTANCE, rng);
+    map.reveal_landmark(|landmark| *landmark == Landmark::Evacuation, rng);
+
+    io.say("\nOver the radio, a crackling voice repeats the location of an evacuation point, marked E on your map.");
+    io.pause();
+    io.say("The rest of the gang are out there somewhere. Find them, and get everyone out.");
+    io.wait();
+}
+
+/// The gang members still waiting to be found.
+pub fn missing(map: &Map) -> Vec<String> {
+    map.unexplored_landmarks()
+        .into_iter()
+        .filter_map(|landmark| match landmark {
+            Landmark::Hideout(name) => Some(name.clone()),
+            Landmark::Evacuation => None,
+        })
+        .collect()
+}
+
+/// Everyone in the gang, Scoob included, who is no longer with the party.
+pub fn lost(party: &Party) -> Vec<String> {
+    GANG.iter()
+        .chain(["Scoob"].iter())
+        .filter(|name| !party.members.iter().any(|member| member.name == **name))
+        .map(|name| (*name).to_owned())
+        .collect()
+}
+
+/// Reveals where one of the hidden gang members is, if any are left to find.
+pub fn clue(map: &mut Map, rng: &mut GameRng, io: &mut dyn Frontend) {
+    let revealed = map.reveal_landmark(|landmark| matches!(landmark, Landmark::Hideout(_)), rng);
+    let Some((Landmark::Hideout(name), bearing, distance)) = revealed else {
+        return;
+    };
+
+    io.say("");
+    io.write(&format!(
+        "Scratched into a wall is a message, in handwriting you would know anywhere. It's from {name}"
+    ));
+    io.ellipsis();
+    io.say(&format!(
+        "\nThey are holed up {distance} tiles to the {bearing}. Their hideout is marked G on your map."
+    ));
+    io.wait();
+}
+
+/// Brings one of the gang back into the party.
+pub fn reunite(name: &str, party: &mut Party, io: &mut dyn Frontend) {
+    let member = Member::gang(name).expect("hideouts to belong to the gang");
+    io.write(&format!(
+        "Behind a barricade of furniture, you find {name}, tired and hungry but alive"
+    ));
+    io.ellipsis();
+    io.say("\nThe reunion lifts everyone's spirits.");
+    io.say(&format!("{member}\n"));
+    party.change_morale(REUNION_MORALE);
+    party.members.push(member);
+}
+
+/// Gets the party out if nobody is left to find. Returns whether they escaped.
+pub fn evacuate(missing: &[String], io: &mut dyn Frontend) -> bool {
+    if !missing.is_empty() {
+        io.say(&format!(
+            "Soldiers wave the party towards the last helicopter out, but you cannot leave without {}.",
+            missing.join(" and ")
+        ));
+        return false;
+    }
+
+    io.write("The party climbs aboard, and the helicopter lifts off over the ruined town");
+    io.ellipsis();
+    io.say("");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, rng};
+
+    #[test]
+    fn gang_is_together_only_with_everyone_alive() {
+        let mut io = Scripted::new(["1"]);
+        let mut party = Party::create(&mut rng::seeded(1), &mut io);
+        for name in ["Shaggy", "Fred", "Daphne"] {
+            reunite(name, &mut party, &mut io);
+        }
+        assert!(lost(&party).is_empty());
+
+        party.members.retain(|member| member.name != "Fred");
+        assert_eq!(lost(&party), ["Fred"]);
+    }
+}
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::{
+    io::{Frontend, Question, View},
+    member::{DeathCheckResult, KILL_XP},
+    morale::PANIC_CHANCE,
+    party::{Cause, Party},
+    relationships::{self, RESCUE_AFFINITY},
+    rng::GameRng,
+    tile::Tile,
+    time::Time,
+    traits::{Trait, RUNNER_FLEE_BONUS},
+    vehicle::{Upgrade, ARMOUR_FLEE_BONUS},
+    weapon::ZOMBIE_HP,
+};
+
+/// The chance of a shot hitting.
+const SHOOT_CHANCE: f64 = 0.85;
+/// The chance of a melee attack hitting.
+const MELEE_CHANCE: f64 = 0.5;
+/// The chance of each zombie landing a hit on its target every round.
+const ZOMBIE_HIT_CHANCE: f64 = 0.75;
+/// How much harder it is to get away in the dark.
+const NIGHT_FLEE_PENALTY: f64 = 0.2;
+/// The chance of the slowest member not making it back to the mystery machine.
+const LEFT_BEHIND_CHANCE: f64 = 0.25;
+/// How long it takes to lose the horde after getting away.
+const FLEE_TIME: i32 = 1;
+/// How much fuel speeding away uses.
+const FLEE_FUEL: i32 = 1;
+/// How much damage the mystery machine takes getting away.
+const FLEE_DAMAGE: i32 = 1;
+
+/// When and where a fight takes place, which decides how easy it is to run
+/// and where the horde is left roaming afterwards.
+pub struct Surroundings<'a> {
+    pub time: &'a mut Time,
+    pub tile: &'a mut Tile,
+    /// Whether the party has left the mystery machine behind, and so has
+    /// nothing to get away in.
+    pub on_foot: bool,
+}
+
+/// How a fight should be played out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Every member picks a move each round.
+    Tactical,
+    /// The fight is resolved automatically, as it always used to be.
+    AutoResolve,
+}
+
+/// How a fight ended.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The zombies were all put down.
+    Won,
+    /// The party got away, leaving the zombies roaming the tile.
+    Fled,
+    /// Nobody in the party is left.
+    Wiped,
+}
+
+/// What a member does during a round of a tactical fight.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    /// Fires the member's gun, using one of its ammo.
+    Shoot,
+    /// Attacks with the member's melee weapon, or bare hands.
+    Melee,
+    /// Takes the hits meant for the member at this index, at half damage.
+    Defend(usize),
+    /// Takes some of the party's medicine.
+    Medicine,
+    /// Tries to get the whole party away from the fight.
+    Flee,
+}
+
+impl Move {
+    const LABELS: &'static [&'static str] = &[
+        "Shoot",
+        "Melee",
+        "Defend another member",
+        "Use medicine",
+        "Flee",
+    ];
+
+    /// The number typed to choose this move. Defending asks who to defend
+    /// separately.
+    pub fn input(self) -> String {
+        let number = match self {
+            Move::Shoot => 1,
+            Move::Melee => 2,
+            Move::Defend(_) => 3,
+            Move::Medicine => 4,
+            Move::Flee => 5,
+        };
+        number.to_string()
+    }
+}
+
+/// Asks how the party wants to fight, then plays the fight out. Returns how
+/// it ended.
+pub fn fight(
+    party: &mut Party,
+    zombies: i32,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Outcome {
+    let outcome = match choose_style(party, zombies, io) {
+        Style::Tactical => tactical(party, zombies, surroundings, rng, io),
+        Style::AutoResolve => {
+            party.auto_resolve(zombies, rng, io);
+            Outcome::Won
+        }
+    };
+    relationships::shared_fight(party);
+    if party.members.is_empty() {
+        Outcome::Wiped
+    } else {
+        outcome
+    }
+}
+
+fn choose_style(party: &Party, zombies: i32, io: &mut dyn Frontend) -> Style {
+    loop {
+        io.say("How do you want to fight?");
+        io.say("(1) Take it one move at a time");
+        io.say("(2) Auto-resolve");
+        let input = io.ask(Question::CombatStyle(View::party(party), zombies), ": ");
+
+        match input.trim() {
+            "1" => return Style::Tactical,
+            "2" => return Style::AutoResolve,
+            _ => io.say("Invalid option."),
+        }
+    }
+}
+
+fn tactical(
+    party: &mut Party,
+    mut zombies: i32,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Outcome {
+    let mut round = 1;
+    // Damage dealt to the zombie at the front, which carries over until it
+    // goes down.
+    let mut wounds = 0;
+    while zombies > 0 && !party.members.is_empty() {
+        io.wait();
+        io.say(&format!(
+            "Round {round}: {zombies} {noun} lurching towards the party.",
+            noun = if zombies == 1 { "zombie" } else { "zombies" }
+        ));
+        for member in party.members.iter() {
+            let weapon = match &member.weapon {
+                Some(weapon) => weapon.to_string(),
+                None => "unarmed".to_owned(),
+            };
+            io.say(&format!(
+                "- {}: {}/{} HP, {} infection, {weapon}{}",
+                member.name,
+                member.hp,
+                member.max_hp,
+                member.infection_level,
+                if member.shaken() { ", shaken" } else { "" }
+            ));
+        }
+        io.say(&format!(
+            "The party has {} ammo, {} bullets and {} medicine.",
+            party.ammo, party.bullets, party.medicine
+        ));
+
+        // Who is standing in for whom this round.
+        let mut defenders: HashMap<usize, usize> = HashMap::new();
+        for index in 0..party.members.len() {
+            if zombies == 0 {
+                break;
+            }
+
+            let name = party.members[index].name.clone();
+            if party.members[index].shaken() && rng.gen_bool(PANIC_CHANCE) {
+                io.say(&format!("\n{name} panics, and freezes in place."));
+                continue;
+            }
+            match choose_move(party, index, zombies, io) {
+                Move::Shoot => {
+                    let weapon = party.members[index]
+                        .weapon
+                        .as_ref()
+                        .expect("member to have a gun");
+                    let (kind, stats) = (weapon.kind, weapon.stats());
+                    *party.ammo_of(stats.ammo.expect("weapon to be a gun")) -= 1;
+                    let mut kills = 0;
+                    if rng.gen_bool(SHOOT_CHANCE) {
+                        let damage = party.members[index]
+                            .gun_damage()
+                            .expect("member to have a gun");
+                        kills = strike(&mut zombies, &mut wounds, damage);
+                        match kills {
+                            0 => io.say(&format!(
+                                "{name} fires the {kind} and hits a zombie, but it keeps coming."
+                            )),
+                            1 => io.say(&format!("{name} fires the {kind}, and a zombie drops.")),
+                            _ => io.say(&format!(
+                                "{name} fires the {kind}, and {kills} zombies drop."
+                            )),
+                        }
+                    } else {
+                        io.say(&format!("{name} fires the {kind}, but misses."));
+                    }
+                    zombies += noise(stats.noise, rng, io);
+                    let member = &mut party.members[index];
+                    member.wear_weapon(io);
+                    member.gain_xp(kills * KILL_XP, rng, io);
+                }
+                Move::Melee => {
+                    let member = &mut party.members[index];
+                    let mut kills = 0;
+                    if rng.gen_bool(MELEE_CHANCE) {
+                        kills = strike(&mut zombies, &mut wounds, member.melee_damage());
+                        if kills > 0 {
+                            io.say(&format!(
+                                "{name} lands a heavy blow, and a zombie stops moving."
+                            ));
+                        } else {
+                            io.say(&format!(
+                                "{name} lands a blow, but the zombie shrugs it off."
+                            ));
+                        }
+                    } else {
+                        io.say(&format!(
+                            "{name} swings wildly, but the zombies keep coming."
+                        ));
+                    }
+                    // Bare hands make no noise and never break.
+                    let melee = member.weapon.as_ref().map(|weapon| weapon.stats());
+                    if let Some(stats) = melee.filter(|stats| stats.ammo.is_none()) {
+                        member.wear_weapon(io);
+                        zombies += noise(stats.noise, rng, io);
+                    }
+                    member.gain_xp(kills * KILL_XP, rng, io);
+                }
+                Move::Defend(target) => {
+                    defenders.insert(target, index);
+                    party
+                        .relationships
+                        .change(&name, &party.members[target].name, RESCUE_AFFINITY);
+                    io.say(&format!(
+                        "{name} stands in front of {}.",
+                        party.members[target].name
+                    ));
+                }
+                Move::Medicine => {
+                    party.medicine -= 1;
+                    let dose = party.dose(rng);
+                    party.members[index].cure(dose);
+                    io.say(&format!("{name} quickly swallows some of the antibiotics."));
+                }
+                Move::Flee => {
+                    io.write(&format!("{name} shouts for everyone to run"));
+                    io.ellipsis();
+                    if let Some((member, casualty)) = relationships::grieving(party, rng) {
+                        io.say(&format!(
+                            "\n{member} refuses to leave {casualty}'s body behind, and the party stays to fight."
+                        ));
+                        io.pause();
+                        continue;
+                    }
+                    let runner = party.has_trait(Trait::Runner);
+                    let armoured = !surroundings.on_foot && party.van.has(Upgrade::ArmourPlating);
+                    if rng.gen_bool(flee_chance(zombies, runner, armoured, surroundings.time)) {
+                        flee(party, zombies, surroundings, rng, io);
+                        return Outcome::Fled;
+                    }
+                    io.say("\nThe zombies cut off the way out.");
+                }
+            }
+            io.pause();
+        }
+
+        if zombies > 0 {
+            zombies += zombie_attack(party, zombies, &defenders, rng, io);
+        }
+
+        io.pause();
+        match zombies.cmp(&1) {
+            std::cmp::Ordering::Greater => {
+                io.say(&format!("There are now {zombies} zombies left..."))
+            }
+            std::cmp::Ordering::Equal => io.say("1 zombie remains..."),
+            std::cmp::Ordering::Less => io.say("The attackers have been defeated..."),
+        }
+        round += 1;
+    }
+    Outcome::Won
+}
+
+/// Deals damage to the zombie at the front, carrying on into the ones behind
+/// it. Returns how many went down.
+fn strike(zombies: &mut i32, wounds: &mut i32, damage: i32) -> i32 {
+    *wounds += damage;
+    let kills = (*wounds / ZOMBIE_HP).min(*zombies);
+    *zombies -= kills;
+    *wounds = if *zombies > 0 { *wounds % ZOMBIE_HP } else { 0 };
+    kills
+}
+
+/// Whether a noisy attack draws another zombie into the fight.
+fn noise(chance: f64, rng: &mut GameRng, io: &mut dyn Frontend) -> i32 {
+    if rng.gen_bool(chance) {
+        io.say("The noise draws another zombie in.");
+        1
+    } else {
+        0
+    }
+}
+
+/// Fewer zombies leave more gaps to slip through, a runner knows when to
+/// go, armour plating keeps the dead off the doors, and the dark makes it
+/// harder to find the way back.
+fn flee_chance(zombies: i32, runner: bool, armoured: bool, time: &Time) -> f64 {
+    let mut bonus = if runner { RUNNER_FLEE_BONUS } else { 0.0 };
+    if armoured {
+        bonus += ARMOUR_FLEE_BONUS;
+    }
+    let penalty = if time.night() {
+        NIGHT_FLEE_PENALTY
+    } else {
+        0.0
+    };
+    (0.9 - 0.1 * zombies as f64 + bonus - penalty).clamp(0.1, 0.9)
+}
+
+/// Gets the party away from the fight. The slowest member may not make it,
+/// and the zombies stay behind, roaming the tile.
+fn flee(
+    party: &mut Party,
+    zombies: i32,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) {
+    io.say("");
+    if party.members.len() > 1 && rng.gen_bool(LEFT_BEHIND_CHANCE) {
+        let slowest = party
+            .members
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, member)| member.hp)
+            .map(|(index, _)| index)
+            .expect("members to be non-empty");
+        let member = party.members.remove(slowest);
+        party.bury(&member, Cause::LeftBehind);
+        io.say(&format!(
+            "{} stumbles, and the zombies close in before anyone can turn back.",
+            member.name
+        ));
+        io.pause();
+    }
+
+    let mut zombies = zombies;
+    if surroundings.on_foot {
+        io.say("With the mystery machine nowhere near, the party scatters and runs.");
+    } else if party.van.broken_down() {
+        io.say("The party makes it back to the mystery machine, but it will not start, so they keep running on foot.");
+    } else if party.fuel >= FLEE_FUEL {
+        party.fuel -= FLEE_FUEL;
+        io.say("The party makes it back to the mystery machine and speeds away.");
+        io.pause();
+        io.say(&format!("-{FLEE_FUEL} fuel"));
+        let rammed = party.van.ram().min(zombies);
+        if rammed > 0 {
+            zombies -= rammed;
+            io.say(&format!(
+                "The ram bar ploughs through {rammed} of the dead on the way out."
+            ));
+        }
+        let damage = party.van.damage(FLEE_DAMAGE);
+        if damage > 0 {
+            io.say(&format!(
+                "The dead batter the mystery machine as it pulls away. -{damage} condition"
+            ));
+        }
+    } else {
+        io.say("The party makes it back to the mystery machine, but with no fuel, it has to keep running on foot.");
+    }
+    surroundings.time.advance(FLEE_TIME);
+    surroundings.tile.horde += zombies;
+    io.pause();
+    io.say("By the time the groaning fades, the party has lost an hour.");
+}
+
+fn choose_move(party: &Party, index: usize, zombies: i32, io: &mut dyn Frontend) -> Move {
+    let name = &party.members[index].name;
+    loop {
+        io.say(&format!("\nWhat does {name} do?"));
+        for (num, label) in Move::LABELS.iter().enumerate() {
+            io.say(&format!("({}) {label}", num + 1));
+        }
+        let question = Question::CombatMove(View::party(party), index, zombies);
+        let input = io.ask(question, ": ");
+
+        match input.trim() {
+            "1" if party.loaded(&party.members[index]) => return Move::Shoot,
+            "1" => io.say(&format!("{name} has no gun, or no ammo for it.")),
+            "2" => return Move::Melee,
+            "3" if party.members.len() > 1 => {
+                if let Some(target) = choose_target(party, index, io) {
+                    return Move::Defend(target);
+                }
+            }
+            "3" => io.say("There is nobody else to defend."),
+            "4" if party.medicine > 0 => return Move::Medicine,
+            "4" => io.say("There is no medicine left."),
+            "5" => return Move::Flee,
+            _ => io.say("Invalid option."),
+        }
+    }
+}
+
+fn choose_target(party: &Party, defender: usize, io: &mut dyn Frontend) -> Option<usize> {
+    loop {
+        io.say(&format!(
+            "Who should {} defend? Enter a number, or `back` to choose another move.",
+            party.members[defender].name
+        ));
+        for (num, member) in party.members.iter().enumerate() {
+            if num != defender {
+                io.say(&format!("({}) {}", num + 1, member.name));
+            }
+        }
+        let input = io.ask(Question::Defend(View::party(party), defender), ": ");
+        let input = input.trim().to_lowercase();
+
+        if input == "back" {
+            return None;
+        }
+        match input
+            .parse::<usize>()
+            .ok()
+            .and_then(|num| num.checked_sub(1))
+        {
+            Some(target) if target < party.members.len() && target != defender => {
+                return Some(target)
+            }
+            _ => io.say("Invalid input."),
+        }
+    }
+}
+
+/// Every zombie goes for a random member. Members who are defended have their
+/// hits taken by their defender at half damage. Returns how many members rose
+/// again to join the horde.
+fn zombie_attack(
+    party: &mut Party,
+    zombies: i32,
+    defenders: &HashMap<usize, usize>,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> i32 {
+    let mut damage = vec![0; party.members.len()];
+    for _ in 0..zombies {
+        let target = rng.gen_range(0..party.members.len());
+        if rng.gen_bool(ZOMBIE_HIT_CHANCE) {
+            let hit = rng.gen_range(1..=2);
+            match defenders.get(&target) {
+                Some(&defender) => damage[defender] += (hit + 1) / 2,
+                None => damage[target] += hit,
+            }
+        }
+    }
+
+    io.wait();
+    if damage.iter().all(|&damage| damage == 0) {
+        io.say("The zombies claw at the party, but nobody is hurt.");
+        return 0;
+    }
+
+    for target in 0..damage.len() {
+        let member = &party.members[target];
+        if damage[target] == 0 || damage[target] < member.hp {
+            continue;
+        }
+        if let Some(devoted) = relationships::sacrifice(party, &member.name, rng) {
+            io.say(&format!(
+                "{} throws themself in front of {}!",
+                party.members[devoted].name, member.name
+            ));
+            damage[devoted] += damage[target];
+            damage[target] = 0;
+        }
+    }
+
+    for (member, &damage) in party.members.iter_mut().zip(damage.iter()) {
+        if damage > 0 {
+            member.hurt(damage, io);
+        }
+    }
+
+    let mut risen = 0;
+    let mut survivors = Vec::new();
+    for member in std::mem::take(&mut party.members) {
+        match member.check_dead() {
+            DeathCheckResult::Alive => survivors.push(member),
+            DeathCheckResult::Dead => {
+                party.bury(&member, Cause::Zombies);
+                io.write(&format!("{} collapses to the ground", member.name));
+                io.ellipsis();
+                io.say("");
+            }
+            DeathCheckResult::Undead => {
+                party.bury(&member, Cause::Zombies);
+                io.write(&format!(
+                    "A horrendous crunch is heard, and {} collapses to the ground",
+                    member.name
+                ));
+                io.ellipsis();
+                io.say(&format!(
+                    "\nA shriek fills the air and {} begins crawling towards the rest of the party...",
+                    member.name
+                ));
+                risen += 1;
+            }
+        }
+    }
+    party.members = survivors;
+
+    risen
+}
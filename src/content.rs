@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use crate::{
+    event_table::{EventTableError, EventTables},
+    location::Locations,
+    mapgen::MapParams,
+    save::data_dir,
+};
+
+const MODS_DIR: &str = "mods";
+/// Where the content hash starts before any data is folded into it.
+pub const EMPTY_HASH: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Everything loaded from data files at startup that decides what can happen
+/// during a run. Runs only play back the same way with the same content.
+pub struct Content {
+    pub events: EventTables,
+    pub locations: Locations,
+    pub map: MapParams,
+}
+
+impl Content {
+    /// Checks that the event tables only override locations that exist.
+    pub fn new(
+        events: EventTables,
+        locations: Locations,
+        map: MapParams,
+    ) -> Result<Self, EventTableError> {
+        events.check_locations(&locations.ids())?;
+        Ok(Self {
+            events,
+            locations,
+            map,
+        })
+    }
+
+    /// Identifies the exact event tables, scenes and map parameters loaded, so
+    /// a run can tell whether it is being played back with the content it was
+    /// recorded with.
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{:016x}{:016x}{:016x}",
+            self.events.hash(),
+            self.locations.hash(),
+            self.map.hash()
+        )
+    }
+
+    /// Loads the event tables from `events`, or the built-in ones, the
+    /// locations from `mods`, or the `mods` directory next to the saves, and
+    /// the map parameters from `map`, or the defaults.
+    pub fn load(
+        events: Option<&Path>,
+        mods: Option<&Path>,
+        map: Option<&Path>,
+    ) -> Result<Self, String> {
+        let events = EventTables::load(events)
+            .map_err(|error| format!("Unable to load the event tables, as {error}."))?;
+        let locations = match mods {
+            Some(mods) => Locations::load(mods),
+            None => Locations::load(&data_dir().join(MODS_DIR)),
+        }
+        .map_err(|error| format!("Unable to load the mods, as {error}."))?;
+        let map = MapParams::load(map)
+            .map_err(|error| format!("Unable to load the map parameters, as {error}."))?;
+
+        Self::new(events, locations, map)
+            .map_err(|error| format!("Unable to load the event tables, as {error}."))
+    }
+}
+
+/// Folds `text` into a running FNV-1a hash. Unlike the standard library's
+/// hashers, this gives the same result on every build, so it can be stored.
+pub fn hash(hash: u64, text: &str) -> u64 {
+    text.bytes().fold(hash, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -1,170 +1,355 @@
-use std::cmp::max;
+use std::{cmp::max, fmt::Display};
 
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::{seq::SliceRandom, Rng};
 
 use crate::{
-    io::{ellipsis, pause, wait},
+    combat::{Outcome, Surroundings},
+    event_table::{EventEntry, EventKind},
+    io::Frontend,
     member::{Member, NamePool},
+    morale::{LOST_MORALE, RECRUIT_MORALE},
     party::Party,
+    rng::GameRng,
+    traits::Trait,
+    weapon::{Weapon, WeaponKind},
 };
 
+#[derive(Clone, Copy)]
 pub enum Event {
     Ammo(i32),
+    Bullets(i32),
     Food(i32),
     Money(i32),
     Fuel(i32),
+    Scrap(i32),
     Zombie(i32),
     Survivor(i32),
+    Weapon(WeaponKind),
     Nothing,
+    Breakdown(i32),
+    Roadblock(i32),
+    Car(i32),
+    Horde(i32),
 }
 
-impl Event {
-    pub fn roll(options: &[Event]) -> Self {
-        let mut rng = thread_rng();
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Event::Ammo(_) => "Ammo",
+            Event::Bullets(_) => "Bullets",
+            Event::Food(_) => "Food",
+            Event::Money(_) => "Money",
+            Event::Fuel(_) => "Fuel",
+            Event::Scrap(_) => "Scrap",
+            Event::Zombie(_) => "Zombies",
+            Event::Survivor(_) => "Survivors",
+            Event::Weapon(_) => "Weapon",
+            Event::Nothing => "Nothing",
+            Event::Breakdown(_) => "Breakdown",
+            Event::Roadblock(_) => "Roadblock",
+            Event::Car(_) => "Abandoned car",
+            Event::Horde(_) => "Horde on the road",
+        };
+        write!(f, "{name}")
+    }
+}
 
-        let event = options
-            .choose(&mut rng)
-            .expect("size of options to be greater than 0");
+impl Event {
+    /// Picks an entry from an event table by weight and rolls its amount. Ammo
+    /// and food are sometimes lost rather than found.
+    pub fn roll(options: &[EventEntry], rng: &mut GameRng) -> Self {
+        let entry = options
+            .choose_weighted(rng, |entry| entry.weight)
+            .expect("event table to have been validated");
 
         let sign = if rng.gen_bool(0.7) { 1 } else { -1 };
+        let amount = match (entry.min, entry.max) {
+            (Some(min), Some(max)) => rng.gen_range(min..=max),
+            _ => 0,
+        };
 
-        match *event {
-            Event::Money(n) => Event::Money(rng.gen_range(1..=n)),
-            Event::Ammo(n) => Event::Ammo(rng.gen_range(1..=n) * sign),
-            Event::Fuel(n) => Event::Fuel(rng.gen_range(1..=n)),
-            Event::Zombie(n) => Event::Zombie(rng.gen_range(1..=n)),
-            Event::Food(n) => Event::Food(rng.gen_range(1..=n) * sign),
-            Event::Survivor(n) => Event::Survivor(rng.gen_range(0..=n)),
-            Event::Nothing => Event::Nothing,
+        match entry.event {
+            EventKind::Money => Event::Money(amount),
+            EventKind::Ammo => Event::Ammo(amount * sign),
+            EventKind::Bullets => Event::Bullets(amount),
+            EventKind::Fuel => Event::Fuel(amount),
+            EventKind::Scrap => Event::Scrap(amount),
+            EventKind::Zombies => Event::Zombie(amount),
+            EventKind::Food => Event::Food(amount * sign),
+            EventKind::Survivors => Event::Survivor(amount),
+            EventKind::Weapon => Event::Weapon(
+                entry
+                    .weapons
+                    .choose_weighted(rng, |weapon| weapon.weight)
+                    .expect("event table to have been validated")
+                    .weapon,
+            ),
+            EventKind::Nothing => Event::Nothing,
+            EventKind::Breakdown => Event::Breakdown(amount),
+            EventKind::Roadblock => Event::Roadblock(amount),
+            EventKind::Car => Event::Car(amount),
+            EventKind::Horde => Event::Horde(amount),
         }
     }
 
-    pub fn handle(&self, party: &mut Party, name_pool: &mut NamePool) {
+    /// Plays the event out. Returns how the fight ended, if it led to one.
+    pub fn handle(
+        &self,
+        party: &mut Party,
+        name_pool: &mut NamePool,
+        surroundings: &mut Surroundings,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> Option<Outcome> {
         match *self {
-            Event::Money(amount) => money(amount, party),
-            Event::Ammo(amount) => ammo(amount, party),
-            Event::Fuel(amount) => fuel(amount, party),
-            Event::Food(amount) => food(amount, party),
-            Event::Zombie(amount) => zombie(amount, party),
-            Event::Survivor(amount) => survivor(amount, party, name_pool),
-            Event::Nothing => nothing(),
+            Event::Money(amount) => money(amount, party, rng, io),
+            Event::Ammo(amount) => ammo(amount, party, io),
+            Event::Bullets(amount) => bullets(amount, party, io),
+            Event::Fuel(amount) => fuel(amount, party, io),
+            Event::Scrap(amount) => scrap(amount, party, io),
+            Event::Food(amount) => food(amount, party, io),
+            Event::Zombie(amount) => return Some(zombie(amount, party, surroundings, rng, io)),
+            Event::Survivor(amount) => survivor(amount, party, name_pool, rng, io),
+            Event::Weapon(kind) => weapon(kind, party, io),
+            Event::Nothing => nothing(party, io),
+            Event::Breakdown(amount) => breakdown(amount, party, io),
+            Event::Roadblock(amount) => roadblock(amount, surroundings, io),
+            Event::Car(amount) => car(amount, party, io),
+            Event::Horde(amount) => return horde(amount, party, surroundings, rng, io),
         }
+        None
     }
 }
 
-fn nothing() {
-    println!("As you walk on, you realise you have lost track of the mystery machine.");
-    pause();
-    print!("You spend the next hour finding your place on the map, the situation slowly chipping away at your sanity");
-    ellipsis();
-    println!();
+fn nothing(party: &mut Party, io: &mut dyn Frontend) {
+    io.say("As you walk on, you realise you have lost track of the mystery machine.");
+    io.pause();
+    io.write("You spend the next hour finding your place on the map, the situation slowly chipping away at your sanity");
+    io.ellipsis();
+    io.say("");
+    party.change_morale(-LOST_MORALE);
 }
 
-fn survivor(amount: i32, party: &mut Party, name_pool: &mut NamePool) {
-    let mut rng = thread_rng();
-
-    print!("In the distance, you spot a column of smoke. The party rushes towards it");
-    ellipsis();
-    println!();
+fn survivor(
+    amount: i32,
+    party: &mut Party,
+    name_pool: &mut NamePool,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) {
+    io.write("In the distance, you spot a column of smoke. The party rushes towards it");
+    io.ellipsis();
+    io.say("");
     if amount > 0 {
         if amount == 1 {
-            println!(
-                "There is a lone survivor waiting by a campfire, they happily join your party."
-            );
+            io.say("There is a lone survivor waiting by a campfire, they happily join your party.");
         } else {
-            println!("There are a group of survivors huddled by the campfire, they happily join your party.");
+            io.say("There are a group of survivors huddled by the campfire, they happily join your party.");
         }
         for _ in 0..amount {
-            wait();
-            let member = Member::new(
-                &name_pool.get(),
+            io.wait();
+            let member = Member::survivor(
+                &name_pool.get(rng),
                 rng.gen_range(10..=20),
                 rng.gen_range(0..=5),
-                Some(rng.gen_range(8..=10)),
+                rng.gen_range(8..=10),
+                rng,
             );
-            println!("You are joined by {}", member);
+            io.say(&format!("You are joined by {}", member));
+            party.change_morale(RECRUIT_MORALE);
             party.members.insert(0, member);
         }
     } else {
-        print!("You finally reach the campfire, but there is nothing here but a few corpses");
-        ellipsis();
-        println!("\nThe bodies are still warm.");
+        io.write("You finally reach the campfire, but there is nothing here but a few corpses");
+        io.ellipsis();
+        io.say("\nThe bodies are still warm.");
     }
 }
 
-fn zombie(amount: i32, party: &mut Party) {
+fn zombie(
+    amount: i32,
+    party: &mut Party,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Outcome {
     if amount > 1 {
-        println!("While searching you hear some groaning nearby, and turn around to see {} zombies lunge towards you!", amount);
+        io.say(&format!("While searching you hear some groaning nearby, and turn around to see {} zombies lunge towards you!", amount));
     } else {
-        println!("While searching you hear some groaning nearby, and turn around to see a zombie lurching towards you!");
+        io.say("While searching you hear some groaning nearby, and turn around to see a zombie lurching towards you!");
     }
-    pause();
-    party.combat(amount);
+    io.pause();
+    party.combat(amount, surroundings, rng, io)
+}
+
+fn weapon(kind: WeaponKind, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("You stumble across an abandoned car, its doors hanging open");
+    io.ellipsis();
+    io.say(&format!(
+        "\nSomeone left a {kind} on the back seat in their hurry to get away."
+    ));
+    io.pause();
+    party.arm(Weapon::new(kind), io);
 }
 
-fn money(amount: i32, party: &mut Party) {
-    print!("You stumble across a corpse. It looks safe to search");
-    ellipsis();
-    println!("\n+{} money", amount);
-    if thread_rng().gen_bool(0.3) {
-        pause();
+fn money(amount: i32, party: &mut Party, rng: &mut GameRng, io: &mut dyn Frontend) {
+    io.write("You stumble across a corpse. It looks safe to search");
+    io.ellipsis();
+    io.say(&format!("\n+{} money", amount));
+    if rng.gen_bool(0.3) {
+        io.pause();
         let mut member = party.members.pop().expect("members");
         member.infection_level += 10;
-        println!(
+        io.say(&format!(
             "{} feels a little dizzy after leaving the corpse...",
             member.name
-        );
+        ));
         party.members.insert(0, member);
     }
     party.money += amount;
 }
 
-fn ammo(amount: i32, party: &mut Party) {
+fn ammo(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
     if amount > 0 {
-        println!(
-            "You manage to break into a park ranger's locker, but there are only a few shells."
-        );
-        pause();
-        println!("Whoever was last here took as much as they could...");
-        pause();
-        println!("+{} ammo", amount);
+        io.say("You manage to break into a park ranger's locker, but there are only a few shells.");
+        io.pause();
+        io.say("Whoever was last here took as much as they could...");
+        io.pause();
+        io.say(&format!("+{} ammo", amount));
     } else if party.ammo > 0 {
-        println!("You hear some rustling in some bushes dead ahead of you.");
-        pause();
-        print!("You fire your shotgun at it out of impluse");
-        ellipsis();
-        println!("\nIt was only a few rats.");
-        pause();
-        println!("{} ammo", amount);
+        io.say("You hear some rustling in some bushes dead ahead of you.");
+        io.pause();
+        io.write("You fire your shotgun at it out of impluse");
+        io.ellipsis();
+        io.say("\nIt was only a few rats.");
+        io.pause();
+        io.say(&format!("{} ammo", amount));
     } else {
-        print!("You hear some rustling in the bushes ahead of you. The dread sets in");
-        ellipsis();
-        println!();
+        io.write("You hear some rustling in the bushes ahead of you. The dread sets in");
+        io.ellipsis();
+        io.say("");
     }
     party.ammo += amount;
 }
 
-fn fuel(amount: i32, party: &mut Party) {
-    print!("You stumble across someone's derelect house. The owners are long gone");
-    ellipsis();
-    println!("\nThere is nothing here, save for a half-empty jerry can of petrol.");
-    pause();
-    println!("+{} fuel", amount);
+fn bullets(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("You search a police car left in the middle of the road");
+    io.ellipsis();
+    io.say("\nThe glovebox holds a half-empty box of bullets.");
+    io.pause();
+    io.say(&format!("+{} bullets", amount));
+    party.bullets += amount;
+}
+
+fn fuel(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("You stumble across someone's derelect house. The owners are long gone");
+    io.ellipsis();
+    io.say("\nThere is nothing here, save for a half-empty jerry can of petrol.");
+    io.pause();
+    io.say(&format!("+{} fuel", amount));
+    party.fuel += amount;
+}
+
+fn breakdown(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("The engine coughs, splutters and dies, and the party spends a tense few minutes under the bonnet");
+    io.ellipsis();
+    let mechanic = party
+        .members
+        .iter()
+        .find(|member| member.has(Trait::Mechanic));
+    let amount = match mechanic {
+        Some(mechanic) => {
+            io.say(&format!(
+                "\n{} gets it running again before anything else can go wrong.",
+                mechanic.name
+            ));
+            amount - 1
+        }
+        None => {
+            io.say("\nIt starts again eventually, but something is rattling that was not before.");
+            amount
+        }
+    };
+    let damage = party.van.damage(amount);
+    if damage > 0 {
+        io.pause();
+        io.say(&format!("-{damage} condition"));
+    }
+}
+
+fn roadblock(amount: i32, surroundings: &mut Surroundings, io: &mut dyn Frontend) {
+    io.write("A tangle of wrecked cars and barbed wire blocks the way ahead");
+    io.ellipsis();
+    if amount > 1 {
+        io.say(&format!(
+            "\nIt takes the party {amount} hours to find a way round."
+        ));
+    } else {
+        io.say("\nIt takes the party an hour to find a way round.");
+    }
+    surroundings.time.advance(amount);
+}
+
+fn car(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("An abandoned car sits at the side of the road, keys still in the ignition");
+    io.ellipsis();
+    io.say(
+        "\nThere is still some petrol left in the tank, and the party siphons off what they can.",
+    );
+    io.pause();
+    io.say(&format!("+{} fuel", amount));
     party.fuel += amount;
 }
 
-fn food(amount: i32, party: &mut Party) {
+fn horde(
+    amount: i32,
+    party: &mut Party,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Option<Outcome> {
+    io.say(&format!(
+        "Round a bend, {amount} zombies are shambling down the middle of the road!"
+    ));
+    io.pause();
+    let mut amount = amount;
+    if !surroundings.on_foot {
+        let rammed = party.van.ram().min(amount);
+        if rammed > 0 {
+            amount -= rammed;
+            io.say(&format!("The ram bar ploughs through {rammed} of them."));
+            io.pause();
+        }
+    }
+    if amount > 0 {
+        Some(party.combat(amount, surroundings, rng, io))
+    } else {
+        io.say("The road is clear again.");
+        None
+    }
+}
+
+fn scrap(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
+    io.write("You pick through the wreck of a car someone drove into a lamppost");
+    io.ellipsis();
+    io.say("\nThere are still a few parts worth saving for the mystery machine.");
+    io.pause();
+    io.say(&format!("+{} scrap", amount));
+    party.scrap += amount;
+}
+
+fn food(amount: i32, party: &mut Party, io: &mut dyn Frontend) {
     if amount > 0 {
-        print!("You stumble across someone's derelect house. The owners are long gone");
-        ellipsis();
-        println!("\nThere is still some old food in the fridge.");
-        pause();
-        println!("+{} food", amount);
+        io.write("You stumble across someone's derelect house. The owners are long gone");
+        io.ellipsis();
+        io.say("\nThere is still some old food in the fridge.");
+        io.pause();
+        io.say(&format!("+{} food", amount));
     } else if party.food > 0 {
         let n = max(amount, -party.food);
-        println!("While searching the area, some of your food spoils and is made inedible...");
-        pause();
-        println!("{} food", n);
+        io.say("While searching the area, some of your food spoils and is made inedible...");
+        io.pause();
+        io.say(&format!("{} food", n));
     }
     party.food += amount;
 }
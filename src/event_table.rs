@@ -0,0 +1,445 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs::read_to_string,
+    io,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    content::{self, EMPTY_HASH},
+    location::Location,
+    terrain::Terrain,
+    time::Time,
+    weapon::WeaponKind,
+};
+
+/// The tables the game ships with, used unless another file is given.
+const BUILT_IN: &str = include_str!("../data/events.json");
+
+/// The kinds of event a table can roll.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum EventKind {
+    Ammo,
+    Bullets,
+    Food,
+    Money,
+    Fuel,
+    Scrap,
+    Zombies,
+    Survivors,
+    Weapon,
+    Nothing,
+    /// Damages the mystery machine. Only happens while driving.
+    Breakdown,
+    /// Loses the party hours finding a way round.
+    Roadblock,
+    /// An abandoned car with fuel left in the tank.
+    Car,
+    /// Zombies blocking the road.
+    Horde,
+}
+
+impl EventKind {
+    /// Whether this can only happen on the way into a tile, in the travel
+    /// tables.
+    fn travel_only(self) -> bool {
+        matches!(
+            self,
+            EventKind::Breakdown | EventKind::Roadblock | EventKind::Car | EventKind::Horde
+        )
+    }
+
+    /// Whether this can only be found by exploring a tile, never on the road.
+    fn explore_only(self) -> bool {
+        matches!(self, EventKind::Weapon | EventKind::Food | EventKind::Money)
+    }
+}
+
+/// One row of an event table. The amount is rolled between `min` and `max`,
+/// inclusive, and events with a higher weight are rolled more often. `Weapon`
+/// events have no amount, and instead find one of `weapons`, picked by weight.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventEntry {
+    pub event: EventKind,
+    pub weight: u32,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    #[serde(default)]
+    pub weapons: Vec<WeaponEntry>,
+}
+
+/// One of the weapons a `Weapon` event can find. Weapons with a higher weight
+/// are found more often.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponEntry {
+    pub weapon: WeaponKind,
+    pub weight: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DayNight {
+    day: Vec<EventEntry>,
+    night: Vec<EventEntry>,
+}
+
+/// Replaces the default day or night table on tiles with a location or of a
+/// terrain. Either can be left out to keep the default.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Overrides {
+    day: Option<Vec<EventEntry>>,
+    night: Option<Vec<EventEntry>>,
+}
+
+/// What can happen on the way into a tile, by time of day and the terrain of
+/// the tile.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TravelTables {
+    default: DayNight,
+    #[serde(default)]
+    terrain: HashMap<Terrain, Overrides>,
+}
+
+#[derive(Debug)]
+pub enum EventTableError {
+    Read(io::Error),
+    Parse(serde_json::Error),
+    /// An entry that parsed, but makes no sense, such as a range that is
+    /// backwards.
+    Invalid {
+        entry: String,
+        reason: String,
+    },
+}
+
+impl Display for EventTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventTableError::Read(error) => write!(f, "it could not be read ({error})"),
+            EventTableError::Parse(error) => write!(f, "it is not valid ({error})"),
+            EventTableError::Invalid { entry, reason } => write!(f, "`{entry}` {reason}"),
+        }
+    }
+}
+
+/// What can happen when exploring a tile, by time of day, location and
+/// terrain. A location's tables take precedence over its terrain's. Without
+/// travel tables, nothing happens on the road.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventTables {
+    default: DayNight,
+    #[serde(default)]
+    locations: HashMap<Location, Overrides>,
+    #[serde(default)]
+    terrain: HashMap<Terrain, Overrides>,
+    #[serde(default)]
+    travel: Option<TravelTables>,
+    /// A hash of the file the tables were parsed from.
+    #[serde(skip)]
+    hash: u64,
+}
+
+impl EventTables {
+    pub fn built_in() -> Self {
+        Self::parse(BUILT_IN)
+            .unwrap_or_else(|error| panic!("built-in event tables to be valid, but {error}"))
+    }
+
+    /// Reads the tables from `path`, or the built-in tables if there is none.
+    pub fn load(path: Option<&Path>) -> Result<Self, EventTableError> {
+        match path {
+            Some(path) => Self::parse(&read_to_string(path).map_err(EventTableError::Read)?),
+            None => Ok(Self::built_in()),
+        }
+    }
+
+    fn parse(json: &str) -> Result<Self, EventTableError> {
+        let mut tables: Self = serde_json::from_str(json).map_err(EventTableError::Parse)?;
+        tables.validate()?;
+        tables.hash = content::hash(EMPTY_HASH, json);
+        Ok(tables)
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn validate(&self) -> Result<(), EventTableError> {
+        validate_table("default.day", &self.default.day, false)?;
+        validate_table("default.night", &self.default.night, false)?;
+        for (location, overrides) in self.locations.iter() {
+            if let Some(day) = &overrides.day {
+                validate_table(&format!("locations.{location}.day"), day, false)?;
+            }
+            if let Some(night) = &overrides.night {
+                validate_table(&format!("locations.{location}.night"), night, false)?;
+            }
+        }
+        validate_terrain("terrain", &self.terrain, false)?;
+        if let Some(travel) = &self.travel {
+            validate_table("travel.default.day", &travel.default.day, true)?;
+            validate_table("travel.default.night", &travel.default.night, true)?;
+            validate_terrain("travel.terrain", &travel.terrain, true)?;
+        }
+        Ok(())
+    }
+
+    /// Fails if any of the overrides are for a location that does not exist,
+    /// which is most likely a typo.
+    pub fn check_locations(&self, known: &HashSet<Location>) -> Result<(), EventTableError> {
+        match self
+            .locations
+            .keys()
+            .find(|location| !known.contains(location))
+        {
+            Some(location) => Err(EventTableError::Invalid {
+                entry: format!("locations.{location}"),
+                reason: "is not a known location".to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The table to roll on for a tile with the given location and terrain,
+    /// at the given time.
+    pub fn options(
+        &self,
+        location: Option<&Location>,
+        terrain: Terrain,
+        time: &Time,
+    ) -> &[EventEntry] {
+        let overrides = [
+            location.and_then(|location| self.locations.get(location)),
+            self.terrain.get(&terrain),
+        ];
+        pick(&self.default, overrides, time)
+    }
+
+    /// The table to roll on when travelling into a tile of the given terrain
+    /// at the given time, if there are travel tables.
+    pub fn travel_options(&self, terrain: Terrain, time: &Time) -> Option<&[EventEntry]> {
+        let travel = self.travel.as_ref()?;
+        Some(pick(&travel.default, [travel.terrain.get(&terrain)], time))
+    }
+}
+
+/// The first override that has a table for the time of day, falling back to
+/// the default.
+fn pick<'a, const N: usize>(
+    default: &'a DayNight,
+    overrides: [Option<&'a Overrides>; N],
+    time: &Time,
+) -> &'a [EventEntry] {
+    let mut overrides = overrides.into_iter().flatten();
+    if time.night() {
+        overrides
+            .find_map(|overrides| overrides.night.as_deref())
+            .unwrap_or(&default.night)
+    } else {
+        overrides
+            .find_map(|overrides| overrides.day.as_deref())
+            .unwrap_or(&default.day)
+    }
+}
+
+fn validate_terrain(
+    name: &str,
+    terrain: &HashMap<Terrain, Overrides>,
+    travel: bool,
+) -> Result<(), EventTableError> {
+    for (terrain, overrides) in terrain.iter() {
+        if let Some(day) = &overrides.day {
+            validate_table(&format!("{name}.{terrain}.day"), day, travel)?;
+        }
+        if let Some(night) = &overrides.night {
+            validate_table(&format!("{name}.{terrain}.night"), night, travel)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks every entry of a table, which is one of the travel tables if
+/// `travel` is set.
+fn validate_table(name: &str, entries: &[EventEntry], travel: bool) -> Result<(), EventTableError> {
+    let invalid = |entry: String, reason: &str| {
+        Err(EventTableError::Invalid {
+            entry,
+            reason: reason.to_owned(),
+        })
+    };
+
+    if entries.iter().all(|entry| entry.weight == 0) {
+        return invalid(
+            name.to_owned(),
+            "needs at least one entry with a weight above 0",
+        );
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        let entry_name = format!("{name}[{index}]");
+        // Repeating a row would weight it twice over, which is what `weight`
+        // is for.
+        if entries[..index]
+            .iter()
+            .any(|earlier| earlier.event == entry.event)
+        {
+            return invalid(entry_name, "repeats an event listed earlier in the table");
+        }
+        if !travel && entry.event.travel_only() {
+            return invalid(entry_name, "can only happen in the `travel` tables");
+        }
+        if travel && entry.event.explore_only() {
+            return invalid(
+                entry_name,
+                "can only be found by exploring, not in the `travel` tables",
+            );
+        }
+        if entry.event == EventKind::Weapon && entry.weapons.is_empty() {
+            return invalid(entry_name, "is `Weapon`, so it needs a list of `weapons`");
+        }
+        if entry.event != EventKind::Weapon && !entry.weapons.is_empty() {
+            return invalid(entry_name, "can only have `weapons` if it is `Weapon`");
+        }
+        if !entry.weapons.is_empty() && entry.weapons.iter().all(|weapon| weapon.weight == 0) {
+            return invalid(
+                entry_name,
+                "needs at least one weapon with a weight above 0",
+            );
+        }
+        for (index, weapon) in entry.weapons.iter().enumerate() {
+            if entry.weapons[..index]
+                .iter()
+                .any(|earlier| earlier.weapon == weapon.weapon)
+            {
+                return invalid(
+                    format!("{entry_name}.weapons[{index}]"),
+                    "repeats a weapon listed earlier",
+                );
+            }
+        }
+
+        match (entry.event, entry.min, entry.max) {
+            (EventKind::Nothing | EventKind::Weapon, None, None) => {}
+            (EventKind::Nothing | EventKind::Weapon, _, _) => {
+                return invalid(entry_name, "cannot have a `min` or `max`")
+            }
+            (_, Some(min), Some(max)) => {
+                if min < 0 {
+                    return invalid(entry_name, "has a negative `min`");
+                }
+                if min > max {
+                    return invalid(entry_name, "has a `min` greater than its `max`");
+                }
+                if entry.event != EventKind::Survivors && min == 0 {
+                    return invalid(entry_name, "has a `min` of 0, which only `Survivors` allow");
+                }
+            }
+            _ => return invalid(entry_name, "needs both a `min` and a `max`"),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTHING: &str = r#"{"event":"Nothing","weight":1}"#;
+
+    /// Tables with `day` as the default day table and `travel` as the default
+    /// travel day table, and nothing happening otherwise.
+    fn tables(day: &str, travel: &str) -> Result<EventTables, EventTableError> {
+        EventTables::parse(&format!(
+            r#"{{"default":{{"day":[{day}],"night":[{NOTHING}]}},"travel":{{"default":{{"day":[{travel}],"night":[{NOTHING}]}}}}}}"#
+        ))
+    }
+
+    /// Whether the tables were rejected because of `entry`.
+    fn rejected(tables: Result<EventTables, EventTableError>, entry: &str) -> bool {
+        matches!(tables, Err(EventTableError::Invalid { entry: invalid, .. }) if invalid == entry)
+    }
+
+    #[test]
+    fn built_in_tables_parse() {
+        assert!(EventTables::parse(BUILT_IN).is_ok());
+    }
+
+    #[test]
+    fn minimal_tables_parse() {
+        assert!(tables(NOTHING, NOTHING).is_ok());
+    }
+
+    #[test]
+    fn all_zero_weights_are_rejected() {
+        let day = r#"{"event":"Nothing","weight":0},{"event":"Fuel","weight":0,"min":1,"max":2}"#;
+        assert!(rejected(tables(day, NOTHING), "default.day"));
+    }
+
+    #[test]
+    fn weapon_without_weapons_is_rejected() {
+        let day = r#"{"event":"Weapon","weight":1}"#;
+        assert!(rejected(tables(day, NOTHING), "default.day[0]"));
+    }
+
+    #[test]
+    fn repeated_events_and_weapons_are_rejected() {
+        let fuel = r#"{"event":"Fuel","weight":1,"min":1,"max":2}"#;
+        assert!(rejected(
+            tables(&format!("{fuel},{NOTHING},{fuel}"), NOTHING),
+            "default.day[2]"
+        ));
+
+        let bats = r#"{"event":"Weapon","weight":1,"weapons":[{"weapon":"Bat","weight":1},{"weapon":"Bat","weight":1}]}"#;
+        assert!(rejected(tables(bats, NOTHING), "default.day[0].weapons[1]"));
+        let bat = r#"{"event":"Weapon","weight":1,"weapons":[{"weapon":"Bat","weight":2}]}"#;
+        assert!(tables(bat, NOTHING).is_ok());
+    }
+
+    #[test]
+    fn backwards_range_is_rejected() {
+        let day = r#"{"event":"Fuel","weight":1,"min":3,"max":1}"#;
+        assert!(rejected(tables(day, NOTHING), "default.day[0]"));
+    }
+
+    #[test]
+    fn zero_min_is_only_allowed_for_survivors() {
+        let food = r#"{"event":"Food","weight":1,"min":0,"max":2}"#;
+        assert!(rejected(tables(food, NOTHING), "default.day[0]"));
+
+        let survivors = r#"{"event":"Survivors","weight":1,"min":0,"max":2}"#;
+        assert!(tables(survivors, NOTHING).is_ok());
+    }
+
+    #[test]
+    fn travel_kinds_are_rejected_outside_travel() {
+        for kind in ["Breakdown", "Roadblock", "Car", "Horde"] {
+            let day = format!(r#"{{"event":"{kind}","weight":1,"min":1,"max":2}}"#);
+            assert!(tables(NOTHING, &day).is_ok(), "{kind} in travel");
+            assert!(rejected(tables(&day, NOTHING), "default.day[0]"), "{kind}");
+        }
+    }
+
+    #[test]
+    fn explore_kinds_are_rejected_in_travel() {
+        let weapon = r#"{"event":"Weapon","weight":1,"weapons":[{"weapon":"Pistol","weight":1}]}"#
+            .to_owned();
+        let food = r#"{"event":"Food","weight":1,"min":1,"max":2}"#.to_owned();
+        let money = r#"{"event":"Money","weight":1,"min":1,"max":2}"#.to_owned();
+        for day in [weapon, food, money] {
+            assert!(tables(&day, NOTHING).is_ok(), "{day} when exploring");
+            assert!(
+                rejected(tables(NOTHING, &day), "travel.default.day[0]"),
+                "{day}"
+            );
+        }
+    }
+}
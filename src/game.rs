@@ -1,16 +1,31 @@
-use std::fs::{read_to_string, remove_file, write};
-
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    io::{clear, ellipsis, flush, pause, read_line, wait},
+    campaign,
+    content::Content,
+    io::{Frontend, Question},
     map::Map,
     member::NamePool,
-    party::Party,
+    morale,
+    party::{Casualty, Encounter, Outcome, Party},
+    replay::{Playback, Recording, Replay},
+    rng::{self, GameRng},
+    save::{Saves, Slot},
+    strategy::{Bot, HeuristicStrategy},
     time::Time,
+    upkeep,
 };
 
-const SAVE_LOCATION: &str = "savegame.json";
+/// What happened during a single turn.
+pub struct TurnReport {
+    /// What the party ran into, if they explored, travelled into trouble or
+    /// were attacked at camp.
+    pub encounter: Option<Encounter>,
+    /// How many turns the player asked the autopilot to play, if they did.
+    pub autopilot: Option<usize>,
+    /// The members who died during the turn.
+    pub casualties: Vec<Casualty>,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Game {
@@ -18,75 +33,491 @@ pub struct Game {
     map: Map,
     time: Time,
     name_pool: NamePool,
+    character: String,
+    /// Whether the run has the gang to find and an evacuation point to reach,
+    /// rather than only ending when the party is wiped out.
+    #[serde(default)]
+    campaign: bool,
+    /// Whether the party has escaped, winning the campaign.
+    #[serde(default)]
+    escaped: bool,
+    seed: u64,
+    rng: GameRng,
 }
 
 impl Game {
-    fn new() -> Self {
-        print!("Day 0");
-        ellipsis();
-        println!("\nEver since the outbreak, the gang were completely separated.");
-        pause();
-        println!("With the power grid down, its almost impossible to contact others.");
-        pause();
-        println!("Resources are limited, and the infection gets worse with every passing day.");
-        wait();
+    pub fn new(seed: u64, content: &Content, io: &mut dyn Frontend) -> Self {
+        let mut rng = rng::seeded(seed);
+
+        io.say(&format!("Seed: {seed}\n"));
+        io.write("Day 0");
+        io.ellipsis();
+        io.say("\nEver since the outbreak, the gang were completely separated.");
+        io.pause();
+        io.say("With the power grid down, its almost impossible to contact others.");
+        io.pause();
+        io.say("Resources are limited, and the infection gets worse with every passing day.");
+        io.wait();
+
+        let campaign = Self::choose_mode(io);
+        let party = Party::create(&mut rng, io);
+        let character = party.members[0].name.clone();
+        let mut map = Map::new(&content.map, &content.locations, party.sight(), &mut rng);
+        if campaign {
+            campaign::start(&mut map, &character, &mut rng, io);
+        }
 
         Self {
-            party: Party::create(),
-            map: Map::new(60, 30),
+            party,
+            map,
             time: Time::day_zero(),
             name_pool: NamePool::new(),
+            character,
+            campaign,
+            escaped: false,
+            seed,
+            rng,
         }
     }
 
-    fn load() -> Option<Self> {
-        let result = read_to_string(SAVE_LOCATION).ok()?;
-        let game = serde_json::from_str(&result).ok()?;
+    /// Asks whether to play the campaign, or to survive for as long as
+    /// possible.
+    fn choose_mode(io: &mut dyn Frontend) -> bool {
+        loop {
+            io.say("\nHow do you want to play?");
+            io.say("(1) Campaign: find the rest of the gang, and escape together");
+            io.say("(2) Survival: hold out for as long as you can");
+            let input = io.ask(Question::Mode, ": ");
+
+            match input.trim() {
+                "1" => return true,
+                "2" => return false,
+                _ => io.say("Invalid option."),
+            }
+        }
+    }
 
-        println!("Savegame found.");
-        pause();
-        println!("Would you like to load it? (y/n)");
-        println!("If you select no, the savegame will be erased.");
+    /// Starts a new run, recording the player's input from the very start.
+    fn start(seed: Option<u64>, content: &Content, io: &mut dyn Frontend) -> (Self, Replay) {
+        let seed = seed.unwrap_or_else(rng::random_seed);
+        let mut replay = Replay::new(seed, content);
+        let game = Self::new(seed, content, &mut Recording::new(io, &mut replay));
+
+        (game, replay)
+    }
+
+    /// Lists the save slots and lets the player load one, start a new run in a
+    /// fresh slot, or delete, rename and copy existing slots.
+    ///
+    /// The replay is only returned if the slot has been recorded since the
+    /// start of its run, by this build and with the same content.
+    pub fn title_screen(
+        saves: &Saves,
+        seed: Option<u64>,
+        content: &Content,
+        io: &mut dyn Frontend,
+    ) -> (Self, Slot, Option<Replay>) {
         loop {
-            print!(": ");
-            flush();
+            let slots = saves.slots();
+            if slots.is_empty() {
+                let slot = saves
+                    .slot(&saves.unused_name())
+                    .expect("generated name to be valid");
+                let (game, replay) = Self::start(seed, content, io);
+                return (game, slot, Some(replay));
+            }
 
-            match read_line().to_lowercase().trim() {
-                "y" | "yes" => return game,
-                "n" | "no" => return None,
-                _ => println!("Invalid option."),
+            io.clear();
+            io.say("Savegames:");
+            for (num, slot) in slots.iter().enumerate() {
+                let summary = match slot.load() {
+                    Ok(game) => game.summary(),
+                    Err(_) => "unreadable".to_owned(),
+                };
+                io.say(&format!("({}) {}: {summary}", num + 1, slot.name));
             }
+            io.say("\nEnter a number to load a savegame, or `new [name]` to start a new run.");
+            io.say("Savegames can also be managed with `delete <number>`, `rename <number> <name>` and `copy <number> <name>`.");
+
+            let input = io.prompt(": ");
+            let (command, rest) = input.trim().split_once(' ').unwrap_or((input.trim(), ""));
+            let (number, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            let find = |number: &str| {
+                let index = number.trim().parse::<usize>().ok()?.checked_sub(1)?;
+                slots.get(index)
+            };
+
+            match (command.to_lowercase().as_str(), find(number)) {
+                ("new", _) => {
+                    let name = match rest.trim() {
+                        "" => saves.unused_name(),
+                        name => name.to_owned(),
+                    };
+                    match saves.vacant_slot(&name) {
+                        Ok(slot) => {
+                            io.clear();
+                            let (game, replay) = Self::start(seed, content, io);
+                            return (game, slot, Some(replay));
+                        }
+                        Err(error) => io.say(&error),
+                    }
+                }
+                ("delete", Some(slot)) => {
+                    io.say(&format!(
+                        "Are you sure you want to delete `{}`? (y/n)",
+                        slot.name
+                    ));
+                    if let "y" | "yes" = io.prompt(": ").to_lowercase().trim() {
+                        slot.delete_all();
+                        io.say("Savegame deleted.");
+                    }
+                }
+                ("rename", Some(slot)) => match saves.rename(slot, name) {
+                    Ok(renamed) => io.say(&format!("Renamed to `{}`.", renamed.name)),
+                    Err(error) => io.say(&error),
+                },
+                ("copy", Some(slot)) => match saves.copy(slot, name) {
+                    Ok(copied) => io.say(&format!("Copied to `{}`.", copied.name)),
+                    Err(error) => io.say(&error),
+                },
+                _ => match find(command) {
+                    Some(slot) => match slot.load() {
+                        Ok(game) => {
+                            let slot = saves.slot(&slot.name).expect("listed slot to be valid");
+                            let replay = slot
+                                .load_replay()
+                                .filter(|replay| replay.check(content).is_ok());
+                            return (game, slot, replay);
+                        }
+                        Err(error) => {
+                            io.clear();
+                            io.say(&format!(
+                                "The savegame `{}` could not be loaded, as {error}.",
+                                slot.name
+                            ));
+                            io.pause();
+                            io.say(&format!(
+                                "\nThe file has been left untouched at {}.",
+                                slot.path().display()
+                            ));
+                            io.say("It can be copied elsewhere for safekeeping, or removed with `delete`.");
+                        }
+                    },
+                    None => io.say("Invalid option."),
+                },
+            }
+            io.wait();
         }
     }
 
-    pub fn load_or_new() -> Self {
-        match Self::load() {
-            Some(game) => game,
-            None => Self::new(),
+    /// A one-line description of the run, shown in the load menu.
+    pub fn summary(&self) -> String {
+        let members = self.party.members.len();
+        let mut summary = format!(
+            "Day {day}, {members} {noun}, playing as {character}",
+            day = self.time.day(),
+            noun = if members == 1 { "member" } else { "members" },
+            character = self.character,
+        );
+        if self.campaign {
+            let missing = campaign::missing(&self.map).len();
+            summary += &format!(", {missing} of the gang still missing");
+        }
+        summary
+    }
+
+    pub fn party(&self) -> &Party {
+        &self.party
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    /// Whether anyone in the party is still alive.
+    pub fn alive(&self) -> bool {
+        !self.party.check_failure()
+    }
+
+    /// Whether the party has won the campaign.
+    pub fn escaped(&self) -> bool {
+        self.escaped
+    }
+
+    /// Plays a single turn.
+    pub fn turn(&mut self, content: &Content, io: &mut dyn Frontend) -> TurnReport {
+        let day = self.time.day();
+        let outcome = self.party.display_options(
+            &mut self.time,
+            &mut self.map,
+            &mut self.name_pool,
+            content,
+            &mut self.rng,
+            io,
+        );
+        if let Outcome::Explored(Encounter::Escaped) = outcome {
+            self.escaped = true;
+        }
+        let (encounter, autopilot) = match outcome {
+            Outcome::Explored(encounter) => (Some(encounter), None),
+            Outcome::Autopilot(turns) => (None, Some(turns)),
+            Outcome::Other(encounter) => (encounter, None),
+        };
+
+        let meals = self.time.take_meals();
+        if meals > 0 && self.alive() {
+            upkeep::eat(&mut self.party, meals, io);
+        }
+
+        let days = self.time.day() - day;
+        if days > 0 && self.alive() {
+            self.party.survive_days(days, &mut self.rng, io);
+        }
+
+        let casualties: Vec<Casualty> = self.party.casualties.drain(..).collect();
+        morale::mourn(&mut self.party, &casualties, io);
+
+        TurnReport {
+            encounter,
+            autopilot,
+            casualties,
         }
     }
 
-    pub fn save(&self) {
-        let json = serde_json::to_string(self).expect("serialize to succeed");
-        write(SAVE_LOCATION, json).expect("write to succeed");
+    /// Plays a single turn, recording the input if there is a replay.
+    fn recorded_turn(
+        &mut self,
+        replay: Option<&mut Replay>,
+        content: &Content,
+        io: &mut dyn Frontend,
+    ) -> TurnReport {
+        match replay {
+            Some(replay) => self.turn(content, &mut Recording::new(io, replay)),
+            None => self.turn(content, io),
+        }
     }
 
-    pub fn run(&mut self) {
+    /// Plays until the party is wiped out, autosaving the game and its replay
+    /// after every turn.
+    ///
+    /// While the autopilot is engaged, its decisions are recorded in the replay
+    /// just like the player's.
+    pub fn run(
+        &mut self,
+        slot: &Slot,
+        mut replay: Option<Replay>,
+        content: &Content,
+        io: &mut dyn Frontend,
+    ) {
+        let mut autopilot = 0;
         loop {
-            self.party
-                .display_options(&mut self.time, &mut self.map, &mut self.name_pool);
-            self.save();
-
-            if self.party.check_failure() {
-                let _ = remove_file(SAVE_LOCATION);
-                println!("As the last member collapses to the ground, the surrounding area grows quiet once again...");
-                wait();
-                clear();
-                println!("Final stats for this run:\n{}", self.party);
-                println!("\nMap:\n\n{}", self.map);
-                wait();
+            let autopiloted = autopilot > 0;
+            let report = if autopiloted {
+                autopilot -= 1;
+                let mut strategy = HeuristicStrategy::default();
+                self.recorded_turn(replay.as_mut(), content, &mut Bot::new(&mut strategy, io))
+            } else {
+                self.recorded_turn(replay.as_mut(), content, io)
+            };
+            slot.save(self);
+            if let Some(replay) = &replay {
+                slot.save_replay(replay);
+            }
+
+            if self.escaped {
+                slot.delete();
+                io.clear();
+                self.show_win_screen(io);
+                if replay.is_some() {
+                    io.say(&format!(
+                        "\nA replay of this run has been kept at {}",
+                        slot.replay_path().display()
+                    ));
+                }
+                io.wait();
+                break;
+            }
+
+            if !self.alive() {
+                slot.delete();
+                io.say("As the last member collapses to the ground, the surrounding area grows quiet once again...");
+                io.wait();
+                io.clear();
+                self.show_final_stats(io);
+                if replay.is_some() {
+                    io.say(&format!(
+                        "\nA replay of this run has been kept at {}",
+                        slot.replay_path().display()
+                    ));
+                }
+                io.wait();
                 break;
             }
+
+            if let Some(turns) = report.autopilot {
+                autopilot = turns;
+                io.say(&format!(
+                    "The autopilot takes over for {turns} {noun}.",
+                    noun = if turns == 1 { "turn" } else { "turns" }
+                ));
+                io.wait();
+            } else if autopiloted && !report.casualties.is_empty() {
+                autopilot = 0;
+                io.say("\nThe autopilot has lost a member of the party, and hands control back to you.");
+                io.wait();
+            } else if autopiloted && autopilot == 0 {
+                io.say("\nThe autopilot has finished, and hands control back to you.");
+                io.wait();
+            }
         }
     }
+
+    /// Plays a recorded run back from its seed, stopping once the recording
+    /// runs out at the end of a turn or the party is wiped out.
+    pub fn replay(
+        replay: Replay,
+        full_speed: bool,
+        content: &Content,
+        io: &mut dyn Frontend,
+    ) -> Self {
+        let seed = replay.seed;
+        let mut playback = Playback::new(io, replay, full_speed);
+        let mut game = Self::new(seed, content, &mut playback);
+        while !playback.finished() && game.alive() && !game.escaped {
+            game.turn(content, &mut playback);
+        }
+
+        io.say("\nEnd of replay.");
+        io.wait();
+        io.clear();
+        io.say(&game.time.to_string());
+        game.show_final_stats(io);
+
+        game
+    }
+
+    fn show_win_screen(&self, io: &mut dyn Frontend) {
+        let day = self.time.day();
+        let lost = campaign::lost(&self.party);
+        if lost.is_empty() {
+            io.say("=== THE GANG ESCAPED ===\n");
+            io.say(&format!(
+                "On day {day}, the gang left the outbreak behind them, together again."
+            ));
+        } else {
+            io.say("=== THE PARTY ESCAPED ===\n");
+            io.say(&format!(
+                "On day {day}, the party left the outbreak behind them, but {} did not live to see it.",
+                lost.join(" and ")
+            ));
+        }
+        let survivors: Vec<&str> = self
+            .party
+            .members
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        io.say(&format!("Made it out: {}\n", survivors.join(", ")));
+        self.show_final_stats(io);
+    }
+
+    fn show_final_stats(&self, io: &mut dyn Frontend) {
+        io.say(&format!(
+            "Final stats for this run (seed {}):\n{}",
+            self.seed, self.party
+        ));
+        io.say(&format!("\nMap:\n\n{}", self.map));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event_table::EventTables,
+        io::{Scripted, Silent},
+        location::Locations,
+        mapgen::MapParams,
+        strategy,
+    };
+
+    fn content() -> Content {
+        Content::new(
+            EventTables::built_in(),
+            Locations::built_in(),
+            MapParams::default(),
+        )
+        .expect("content to be valid")
+    }
+
+    /// Records every line a random player types over a full survival run.
+    fn record(seed: u64, content: &Content) -> (Replay, Game) {
+        let mut replay = Replay::new(seed, content);
+        let mut strategy = strategy::by_name("random", seed).expect("strategy to exist");
+        let mut io = Silent;
+        let mut bot = Bot::new(strategy.as_mut(), &mut io);
+        let mut recording = Recording::new(&mut bot, &mut replay);
+        let mut game = Game::new(seed, content, &mut recording);
+        while game.alive() && !game.escaped() {
+            game.turn(content, &mut recording);
+        }
+        (replay, game)
+    }
+
+    /// Plays a run from its seed with nothing but scripted input, until the
+    /// script runs out at the end of a turn.
+    fn play(seed: u64, inputs: &[String], content: &Content) -> (Game, Scripted) {
+        let mut io = Scripted::new(inputs.iter().cloned());
+        let mut game = Game::new(seed, content, &mut io);
+        while !io.finished() && game.alive() && !game.escaped() {
+            game.turn(content, &mut io);
+        }
+        (game, io)
+    }
+
+    #[test]
+    fn scripted_input_starts_a_survival_run() {
+        let content = content();
+        let inputs = ["2", "1", "4", "1", "back"].map(str::to_owned);
+        let (game, io) = play(1, &inputs, &content);
+
+        assert!(io.finished());
+        assert!(!game.campaign);
+        assert_eq!(game.character, "Velma");
+        let names: Vec<&str> = game.party.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Velma", "Scoob"]);
+        assert_eq!(game.party.food, 4 - game.party.members[0].meal());
+        assert!(io.output().contains("Velma begins eating"));
+    }
+
+    #[test]
+    fn scripted_game_plays_to_the_end() {
+        let content = content();
+        let (replay, recorded) = record(7, &content);
+        let (game, io) = play(7, &replay.inputs, &content);
+
+        assert!(io.finished());
+        assert!(!game.alive());
+        assert_eq!(game.time.to_string(), "Day 1: 23:00 (NIGHT)");
+        assert_eq!(game.time.to_string(), recorded.time.to_string());
+        assert_eq!(game.party.to_string(), recorded.party.to_string());
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_the_same_transcript() {
+        let content = content();
+        let (replay, _) = record(11, &content);
+        let (first, first_io) = play(11, &replay.inputs, &content);
+        let (second, second_io) = play(11, &replay.inputs, &content);
+
+        assert_eq!(first_io.output(), second_io.output());
+        assert_eq!(
+            serde_json::to_string(&first).expect("serialize to succeed"),
+            serde_json::to_string(&second).expect("serialize to succeed")
+        );
+    }
 }
@@ -3,45 +3,304 @@ use crossterm::{
     terminal::{Clear, ClearType},
     QueueableCommand,
 };
-use std::io::{stdin, stdout, Write};
+use std::collections::VecDeque;
+use std::io::{stdin, stdout, IsTerminal, Write};
 use std::thread;
 use std::time::Duration;
 
-pub fn pause() {
-    thread::sleep(Duration::from_millis(850));
+use crate::{
+    map::Map,
+    party::Party,
+    settings::{Settings, TextSpeed},
+    time::Time,
+    weapon::Weapon,
+};
+
+const PAUSE: Duration = Duration::from_millis(850);
+const ELLIPSIS_DOT: Duration = Duration::from_millis(700);
+
+/// A decision the game asks the player to make, along with what they can see
+/// while making it. Automated players answer these instead of reading the
+/// prompt text.
+#[derive(Clone, Copy)]
+pub enum Question<'a> {
+    /// Which kind of run to play: `1` for the campaign, `2` for survival.
+    Mode,
+    /// Which of the gang to play as, by number.
+    Character,
+    /// The next action from the main menu, by number.
+    Action(View<'a>),
+    /// A compass direction to travel in, or `back`.
+    Direction(View<'a>),
+    /// Which party member to feed, by number, or `back`.
+    Feed(View<'a>),
+    /// Which party member to give medicine to, by number, or `back`.
+    Cure(View<'a>),
+    /// Whether to do something, answered with `y` or `n`.
+    Confirm(View<'a>, &'a str),
+    /// How much money to spend on something, up to what the party has.
+    Spend(View<'a>, &'a str),
+    /// How to fight this many zombies: `1` for move by move, `2` to
+    /// auto-resolve.
+    CombatStyle(View<'a>, i32),
+    /// What the member at this index does this round of a fight against this
+    /// many zombies, by number.
+    CombatMove(View<'a>, usize, i32),
+    /// Which member the member at this index defends, by number, or `back`.
+    Defend(View<'a>, usize),
+    /// How food is shared out at meal times, by number.
+    Rations(View<'a>),
+    /// Which member takes a weapon the party has found, by number, or `leave`.
+    Equip(View<'a>, &'a Weapon),
+    /// Who keeps this watch of the night, by number, or `none`.
+    Watch(View<'a>, usize),
+    /// Whether to leave the mystery machine behind and go on, answered with
+    /// `y`/`yes` or `n`/`no`.
+    LeaveVan(View<'a>),
+    /// What work to do on the mystery machine, by number, or `back`.
+    Van(View<'a>),
 }
 
-pub fn wait() {
-    print!("→");
-    flush();
-    read_line();
-    stdout()
-        .queue(MoveToPreviousLine(1))
-        .expect("cursor to be moveable");
-    stdout()
-        .queue(Clear(ClearType::CurrentLine))
-        .expect("line to be clearable");
-    println!();
+/// What the player can see while making a decision.
+#[derive(Clone, Copy)]
+pub struct View<'a> {
+    pub party: &'a Party,
+    pub map: Option<&'a Map>,
+    pub time: Option<&'a Time>,
 }
 
-pub fn ellipsis() {
-    for _ in 0..3 {
-        print!(".");
-        flush();
-        thread::sleep(Duration::from_millis(700));
+impl<'a> View<'a> {
+    pub fn new(party: &'a Party, map: &'a Map, time: &'a Time) -> Self {
+        Self {
+            party,
+            map: Some(map),
+            time: Some(time),
+        }
+    }
+
+    /// A view of just the party, for decisions made away from the map.
+    pub fn party(party: &'a Party) -> Self {
+        Self {
+            party,
+            map: None,
+            time: None,
+        }
     }
 }
 
-pub fn clear() {
-    print!("{esc}c", esc = 27 as char);
+/// Everything the game needs in order to talk to the player.
+///
+/// Game code never touches stdin or stdout directly, so the same run can be
+/// played in a terminal or driven headlessly from a script.
+pub trait Frontend {
+    /// Writes text without starting a new line.
+    fn write(&mut self, text: &str);
+
+    /// Reads the next line entered by the player.
+    fn read_line(&mut self) -> String;
+
+    /// Waits for the player to acknowledge what has been shown so far.
+    fn wait(&mut self);
+
+    /// Clears the screen.
+    fn clear(&mut self);
+
+    /// Holds the output for a moment, for dramatic effect.
+    fn sleep(&mut self, duration: Duration);
+
+    /// How quickly text is currently being shown.
+    fn text_speed(&self) -> TextSpeed {
+        TextSpeed::Instant
+    }
+
+    /// Changes the text speed, for frontends that have one.
+    fn set_text_speed(&mut self, _speed: TextSpeed) {}
+
+    /// Writes a full line of text.
+    fn say(&mut self, text: &str) {
+        self.write(text);
+        self.write("\n");
+    }
+
+    /// Writes `text` and returns the line entered in response.
+    fn prompt(&mut self, text: &str) -> String {
+        self.write(text);
+        self.read_line()
+    }
+
+    /// Asks the player to make a decision, returning the line they entered.
+    /// Frontends that play by themselves can answer from the question instead
+    /// of waiting for input.
+    fn ask(&mut self, _question: Question, text: &str) -> String {
+        self.prompt(text)
+    }
+
+    fn pause(&mut self) {
+        self.sleep(PAUSE);
+    }
+
+    fn ellipsis(&mut self) {
+        for _ in 0..3 {
+            self.write(".");
+            self.sleep(ELLIPSIS_DOT);
+        }
+    }
 }
 
-pub fn flush() {
-    stdout().flush().unwrap();
+/// Plays the game in the terminal the program was started from.
+///
+/// When stdout is not a terminal, such as when it is piped into a file, text
+/// is always shown instantly and no cursor movement is emitted.
+pub struct Terminal {
+    settings: Settings,
+    interactive: bool,
 }
 
-pub fn read_line() -> String {
-    let mut input = String::new();
-    stdin().read_line(&mut input).expect("valid input");
-    input
+impl Terminal {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            interactive: stdout().is_terminal(),
+        }
+    }
+}
+
+impl Frontend for Terminal {
+    fn write(&mut self, text: &str) {
+        print!("{text}");
+        stdout().flush().unwrap();
+    }
+
+    fn read_line(&mut self) -> String {
+        let mut input = String::new();
+        stdin().read_line(&mut input).expect("valid input");
+        input
+    }
+
+    fn wait(&mut self) {
+        self.write("→");
+        self.read_line();
+        if !self.interactive {
+            return;
+        }
+        stdout()
+            .queue(MoveToPreviousLine(1))
+            .expect("cursor to be moveable");
+        stdout()
+            .queue(Clear(ClearType::CurrentLine))
+            .expect("line to be clearable");
+        println!();
+    }
+
+    fn clear(&mut self) {
+        self.write(&format!("{esc}c", esc = 27 as char));
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        // The setting is kept as it is, so it still shows in the settings
+        // menu, but nobody is watching the text appear.
+        if !self.interactive {
+            return;
+        }
+        let duration = self.text_speed().scale(duration);
+        if !duration.is_zero() {
+            thread::sleep(duration);
+        }
+    }
+
+    fn text_speed(&self) -> TextSpeed {
+        self.settings.text_speed
+    }
+
+    fn set_text_speed(&mut self, speed: TextSpeed) {
+        self.settings.text_speed = speed;
+        self.settings.save();
+    }
+}
+
+/// Feeds the game a fixed list of input lines and captures everything it
+/// writes, without ever sleeping or touching the terminal.
+#[derive(Default)]
+pub struct Scripted {
+    input: VecDeque<String>,
+    output: String,
+}
+
+impl Scripted {
+    pub fn new<I, S>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            input: lines.into_iter().map(Into::into).collect(),
+            output: String::new(),
+        }
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Whether every input line has been read.
+    pub fn finished(&self) -> bool {
+        self.input.is_empty()
+    }
+}
+
+impl Frontend for Scripted {
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn read_line(&mut self) -> String {
+        let line = self.input.pop_front().expect("scripted input to remain");
+        self.output.push_str(&line);
+        self.output.push('\n');
+        line
+    }
+
+    fn wait(&mut self) {
+        self.output.push('\n');
+    }
+
+    fn clear(&mut self) {
+        self.output.push('\n');
+    }
+
+    fn sleep(&mut self, _duration: Duration) {}
+}
+
+/// Discards everything the game writes, for games played entirely by an
+/// automated player.
+pub struct Silent;
+
+impl Frontend for Silent {
+    fn write(&mut self, _text: &str) {}
+
+    fn read_line(&mut self) -> String {
+        panic!("a silent frontend has no player to read input from");
+    }
+
+    fn wait(&mut self) {}
+
+    fn clear(&mut self) {}
+
+    fn sleep(&mut self, _duration: Duration) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_reports_the_saved_speed_when_not_interactive() {
+        let terminal = Terminal {
+            settings: Settings {
+                text_speed: TextSpeed::Fast,
+            },
+            interactive: false,
+        };
+        assert!(terminal.text_speed() == TextSpeed::Fast);
+    }
 }
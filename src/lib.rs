@@ -0,0 +1,32 @@
+pub mod args;
+pub mod camp;
+pub mod campaign;
+pub mod combat;
+pub mod content;
+pub mod event;
+pub mod event_table;
+pub mod game;
+pub mod io;
+pub mod location;
+pub mod map;
+pub mod mapgen;
+pub mod member;
+pub mod migration;
+pub mod morale;
+pub mod party;
+pub mod relationships;
+pub mod replay;
+pub mod rng;
+pub mod save;
+pub mod scene;
+pub mod settings;
+pub mod simulation;
+pub mod strategy;
+pub mod terrain;
+pub mod tile;
+pub mod time;
+pub mod traits;
+pub mod travel;
+pub mod upkeep;
+pub mod vehicle;
+pub mod weapon;
@@ -1,18 +1,69 @@
-use game::Game;
-use io::clear;
-
-mod event;
-mod game;
-mod io;
-mod location;
-mod map;
-mod member;
-mod party;
-mod tile;
-mod time;
+use std::process::exit;
+
+use zombie_apocalypse::{
+    args::Args,
+    content::Content,
+    game::Game,
+    io::{Frontend, Terminal},
+    replay::Replay,
+    save::Saves,
+    settings::Settings,
+};
 
 fn main() {
-    clear();
-    let mut game = Game::load_or_new();
-    game.run();
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!("Usage: zombie-apocalypse [--seed <u64>] [--save-dir <path>] [--replay <file> [--full-speed]] [--events <file>] [--mods <dir>] [--map <file>]");
+            exit(1);
+        }
+    };
+
+    let content = match Content::load(
+        args.events.as_deref(),
+        args.mods.as_deref(),
+        args.map.as_deref(),
+    ) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("{error}");
+            exit(1);
+        }
+    };
+
+    let mut io = Terminal::new(Settings::load());
+
+    if let Some(path) = args.replay {
+        let replay = match Replay::load(&path) {
+            Ok(replay) => replay,
+            Err(error) => {
+                eprintln!("Unable to read the replay `{}`: {error}", path.display());
+                exit(1);
+            }
+        };
+        if let Err(error) = replay.check(&content) {
+            eprintln!(
+                "Unable to play back the replay `{}`, as {error}.",
+                path.display()
+            );
+            exit(1);
+        }
+
+        io.clear();
+        Game::replay(replay, args.full_speed, &content, &mut io);
+        return;
+    }
+
+    let saves = match Saves::open(args.save_dir) {
+        Ok(saves) => saves,
+        Err(error) => {
+            eprintln!("Unable to open the save directory: {error}");
+            exit(1);
+        }
+    };
+
+    io.clear();
+    let (mut game, slot, replay) = Game::title_screen(&saves, args.seed, &content, &mut io);
+    game.run(&slot, replay, &content, &mut io);
 }
@@ -1,13 +1,25 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fmt::Display;
 use std::fmt::Write;
 
-use rand::{thread_rng, Rng};
+use rand::seq::IteratorRandom;
 use serde::Deserialize;
 use serde::Serialize;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
-use crate::tile::Tile;
+use crate::{
+    campaign::Landmark,
+    location::Locations,
+    mapgen::{self, MapParams},
+    rng::GameRng,
+    terrain::Terrain,
+    tile::Tile,
+    vehicle::Vehicle,
+};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
     x: i32,
     y: i32,
@@ -19,8 +31,13 @@ pub struct Map {
     width: i32,
     height: i32,
     rows: Vec<Vec<Tile>>,
+    /// Where the mystery machine is parked while the party is away from it
+    /// on foot.
+    #[serde(default)]
+    van: Option<Position>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
 pub enum Direction {
     North,
     South,
@@ -28,32 +45,44 @@ pub enum Direction {
     West,
 }
 
-impl Map {
-    pub fn new(width: i32, height: i32) -> Self {
-        let mut rng = thread_rng();
-
-        let mut rows = Vec::new();
-        for _ in 0..height {
-            let mut row = Vec::new();
-            for _ in 0..width {
-                let tile = Tile::random();
-                row.push(tile);
-            }
-            rows.push(row);
+impl Direction {
+    /// The word typed to travel in this direction.
+    pub fn input(self) -> &'static str {
+        match self {
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::East => "east",
+            Direction::West => "west",
         }
+    }
 
-        let position = Position {
-            x: rng.gen_range(0..width),
-            y: rng.gen_range(0..height),
-        };
+    pub fn from_input(input: &str) -> Option<Self> {
+        Direction::iter().find(|direction| direction.input() == input.trim())
+    }
+}
+
+impl Map {
+    /// A new generated map, with the party starting somewhere off the rivers
+    /// and the tiles within `sight` of them revealed.
+    pub fn new(params: &MapParams, locations: &Locations, sight: i32, rng: &mut GameRng) -> Self {
+        let (width, height) = (params.width, params.height);
+        let rows = mapgen::generate(params, locations, rng);
+
+        let (x, y) = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| rows[y as usize][x as usize].terrain != Terrain::River)
+            .choose(rng)
+            .expect("map to have dry land");
+        let position = Position { x, y };
 
         let mut map = Self {
             position,
             rows,
             width,
             height,
+            van: None,
         };
-        map.check_surroundings();
+        map.check_surroundings(sight);
 
         map
     }
@@ -73,7 +102,7 @@ impl Map {
         &self.rows[position.y as usize][position.x as usize]
     }
 
-    fn get_tile_mut(&mut self, pos: Option<&Position>) -> &mut Tile {
+    pub fn get_tile_mut(&mut self, pos: Option<&Position>) -> &mut Tile {
         let position;
         if let Some(pos) = pos {
             position = pos;
@@ -84,9 +113,9 @@ impl Map {
         &mut self.rows[position.y as usize][position.x as usize]
     }
 
-    fn check_surroundings(&mut self) {
-        for x in -1..=1 {
-            for y in -1..=1 {
+    fn check_surroundings(&mut self, sight: i32) {
+        for x in -sight..=sight {
+            for y in -sight..=sight {
                 let pos = Position {
                     x: (self.position.x + x).rem_euclid(self.width),
                     y: (self.position.y + y).rem_euclid(self.height),
@@ -97,41 +126,253 @@ impl Map {
         }
     }
 
-    pub fn travel(&mut self, direction: Direction) {
-        match direction {
-            Direction::North => self.position.y -= 1,
-            Direction::South => self.position.y += 1,
-            Direction::East => self.position.x += 1,
-            Direction::West => self.position.x -= 1,
+    /// How far east and south a tile is from the party, the short way round.
+    fn offset(&self, x: usize, y: usize) -> (i32, i32) {
+        let wrap = |delta: i32, size: i32| {
+            let delta = delta.rem_euclid(size);
+            if delta > size / 2 {
+                delta - size
+            } else {
+                delta
+            }
+        };
+        (
+            wrap(x as i32 - self.position.x, self.width),
+            wrap(y as i32 - self.position.y, self.height),
+        )
+    }
+
+    /// The first step towards the cheapest other tile to reach matching
+    /// `target`, and how much fuel it takes to drive there. Routes follow the
+    /// roads where that saves fuel, go round rivers and may wrap around the
+    /// edges.
+    pub fn route(&self, van: &Vehicle, target: impl Fn(&Tile) -> bool) -> Option<(Direction, i32)> {
+        self.search(Some(van), |_, tile| target(tile))
+    }
+
+    /// The first step towards the quickest other tile to walk to matching
+    /// `target`, and how many hours it takes to get there.
+    pub fn walking_route(&self, target: impl Fn(&Tile) -> bool) -> Option<(Direction, i32)> {
+        self.search(None, |_, tile| target(tile))
+    }
+
+    /// The first step of the walk back to the mystery machine, and how many
+    /// hours it takes, if the party has left it behind.
+    pub fn van_route(&self) -> Option<(Direction, i32)> {
+        let van = self.van?;
+        self.search(None, |position, _| *position == van)
+    }
+
+    /// Finds the cheapest way to a tile matching `target`, counting fuel when
+    /// driving `van` and hours when on foot.
+    fn search(
+        &self,
+        van: Option<&Vehicle>,
+        target: impl Fn(&Position, &Tile) -> bool,
+    ) -> Option<(Direction, i32)> {
+        let cost = |tile: &Tile| match van {
+            None => Some(tile.walk_time()),
+            Some(van) => tile.drivable().then(|| van.fuel_cost(tile.fuel_cost())),
+        };
+        let index = |position: &Position| (position.y * self.width + position.x) as usize;
+        let mut best = vec![i32::MAX; (self.width * self.height) as usize];
+        let mut queue = BinaryHeap::new();
+        best[index(&self.position)] = 0;
+        for direction in Direction::iter() {
+            let position = self.step(direction);
+            if let Some(cost) = cost(self.get_tile(Some(&position))) {
+                queue.push(Reverse((cost, position.x, position.y, direction)));
+            }
+        }
+
+        while let Some(Reverse((total, x, y, first))) = queue.pop() {
+            let position = Position { x, y };
+            if total >= best[index(&position)] {
+                continue;
+            }
+            best[index(&position)] = total;
+            if target(&position, self.get_tile(Some(&position))) {
+                return Some((first, total));
+            }
+            for direction in Direction::iter() {
+                let next = self.step_from(&position, direction);
+                let Some(cost) = cost(self.get_tile(Some(&next))) else {
+                    continue;
+                };
+                if total + cost < best[index(&next)] {
+                    queue.push(Reverse((total + cost, next.x, next.y, first)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Puts a landmark on a random ordinary tile at least `min_distance` away
+    /// from the party. Should the locations have taken every such tile, it
+    /// goes on the best of the rest: an ordinary tile if there is one, and as
+    /// far away as can be.
+    pub fn place_landmark(&mut self, landmark: Landmark, min_distance: i32, rng: &mut GameRng) {
+        let candidates: Vec<((usize, usize), (bool, i32))> = (0..self.height as usize)
+            .flat_map(|y| (0..self.width as usize).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let tile = &self.rows[y][x];
+                let (dx, dy) = self.offset(x, y);
+                let distance = dx.abs() + dy.abs();
+                (tile.landmark.is_none() && tile.terrain != Terrain::River && distance > 0)
+                    .then_some((
+                        (x, y),
+                        (tile.location_type.is_none(), distance.min(min_distance)),
+                    ))
+            })
+            .collect();
+        let best = candidates
+            .iter()
+            .map(|(_, rank)| *rank)
+            .max()
+            .expect("map parameters to leave enough dry land for the landmarks");
+        let (x, y) = candidates
+            .into_iter()
+            .filter(|(_, rank)| *rank == best)
+            .map(|(tile, _)| tile)
+            .choose(rng)
+            .expect("a tile to have the best rank");
+        self.rows[y][x].landmark = Some(landmark);
+    }
+
+    /// Marks a random unseen landmark matching `target` on the map, and
+    /// returns it with its compass bearing and distance from the party.
+    pub fn reveal_landmark(
+        &mut self,
+        target: impl Fn(&Landmark) -> bool,
+        rng: &mut GameRng,
+    ) -> Option<(Landmark, String, i32)> {
+        let (x, y) = (0..self.height as usize)
+            .flat_map(|y| (0..self.width as usize).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                let tile = &self.rows[y][x];
+                !tile.seen && tile.landmark.as_ref().is_some_and(&target)
+            })
+            .choose(rng)?;
+
+        let tile = &mut self.rows[y][x];
+        tile.seen = true;
+        let landmark = tile.landmark.clone().expect("tile to have a landmark");
+
+        let (dx, dy) = self.offset(x, y);
+        let north_south = match dy.signum() {
+            -1 => "north",
+            1 => "south",
+            _ => "",
+        };
+        let east_west = match dx.signum() {
+            1 => "east",
+            -1 => "west",
+            _ => "",
+        };
+        let bearing = match (north_south, east_west) {
+            ("", bearing) | (bearing, "") => bearing.to_owned(),
+            (north_south, east_west) => format!("{north_south}-{east_west}"),
         };
 
-        self.position = Position {
-            x: self.position.x.rem_euclid(self.width),
-            y: self.position.y.rem_euclid(self.height),
+        Some((landmark, bearing, dx.abs() + dy.abs()))
+    }
+
+    /// Every landmark the party has yet to explore.
+    pub fn unexplored_landmarks(&self) -> Vec<&Landmark> {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|tile| !tile.explored)
+            .filter_map(|tile| tile.landmark.as_ref())
+            .collect()
+    }
+
+    /// Where one step in `direction` leads from the party, wrapping around
+    /// the edges.
+    fn step(&self, direction: Direction) -> Position {
+        self.step_from(&self.position, direction)
+    }
+
+    fn step_from(&self, position: &Position, direction: Direction) -> Position {
+        let (x, y) = match direction {
+            Direction::North => (position.x, position.y - 1),
+            Direction::South => (position.x, position.y + 1),
+            Direction::East => (position.x + 1, position.y),
+            Direction::West => (position.x - 1, position.y),
         };
 
-        self.check_surroundings();
+        Position {
+            x: x.rem_euclid(self.width),
+            y: y.rem_euclid(self.height),
+        }
+    }
+
+    /// The tile one step away in `direction`.
+    pub fn neighbour(&self, direction: Direction) -> &Tile {
+        self.get_tile(Some(&self.step(direction)))
+    }
+
+    pub fn travel(&mut self, direction: Direction, sight: i32) {
+        self.position = self.step(direction);
+        self.check_surroundings(sight);
+    }
+
+    /// Whether the party has left the mystery machine behind.
+    pub fn on_foot(&self) -> bool {
+        self.van.is_some()
+    }
+
+    /// Parks the mystery machine where the party is, and sets off without it.
+    pub fn leave_van(&mut self) {
+        self.van = Some(self.position);
+    }
+
+    /// Gets back into the mystery machine if the party is standing next to it.
+    /// Returns whether they did.
+    pub fn board_van(&mut self) -> bool {
+        if self.van != Some(self.position) {
+            return false;
+        }
+        self.van = None;
+        true
     }
 }
 
 impl Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         const KEY: &[&str] = &[
-            ".  Unexplored",
             "?  Point of Interest",
             "#  Explored",
             "X  Explored Point of Interest",
             "M  Mystery Machine",
+            "@  Party on Foot",
+            "G  Gang Hideout",
+            "E  Evacuation Point",
+            "!  Horde",
+            "",
+            "=  City",
+            ":  Suburbs",
+            "^  Forest",
+            ".  Farmland",
+            "~  River",
+            "+  Road",
         ];
 
         let mut output = String::new();
         for (y, row) in self.rows.iter().enumerate() {
             write!(output, " ")?;
             for (x, tile) in row.iter().enumerate() {
-                if x as i32 != self.position.x || y as i32 != self.position.y {
-                    write!(output, "{tile}")?;
-                } else {
+                let position = Position {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                if position == self.position && self.on_foot() {
+                    write!(output, "@")?;
+                } else if position == self.position || Some(position) == self.van {
                     write!(output, "M")?;
+                } else {
+                    write!(output, "{tile}")?;
                 }
             }
             if y < KEY.len() {
@@ -143,3 +384,55 @@ impl Display for Map {
         write!(f, "{}", output)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{campaign, io::Scripted, rng};
+
+    #[test]
+    fn every_location_and_landmark_can_be_reached_from_the_start() {
+        for seed in 0..20 {
+            let mut rng = rng::seeded(seed);
+            let mut map = Map::new(&MapParams::default(), &Locations::built_in(), 1, &mut rng);
+            campaign::start(&mut map, "Velma", &mut rng, &mut Scripted::default());
+
+            for (y, row) in map.rows.iter().enumerate() {
+                for (x, tile) in row.iter().enumerate() {
+                    let target = Position {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    if target == map.position
+                        || (tile.location_type.is_none() && tile.landmark.is_none())
+                    {
+                        continue;
+                    }
+                    assert!(
+                        map.search(Some(&Vehicle::default()), |position, _| *position == target)
+                            .is_some(),
+                        "seed {seed} cut off the tile at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn landmarks_fit_on_a_map_too_small_to_keep_them_apart() {
+        let mut params = MapParams::default();
+        params.width = 6;
+        params.height = 6;
+        params.rivers = 0;
+        params.location_chances = [1.0; 6];
+        let mut rng = rng::seeded(1);
+        let mut map = Map::new(&params, &Locations::built_in(), 1, &mut rng);
+        campaign::start(&mut map, "Velma", &mut rng, &mut Scripted::default());
+        let landmarks = map
+            .rows
+            .iter()
+            .flatten()
+            .filter(|tile| tile.landmark.is_some());
+        assert_eq!(landmarks.count(), campaign::LANDMARKS);
+    }
+}
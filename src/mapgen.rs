@@ -0,0 +1,434 @@
+use std::{collections::VecDeque, fs::read_to_string, path::Path};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    campaign::{LANDMARKS, MIN_DISTANCE},
+    content::{self, EMPTY_HASH},
+    location::Locations,
+    rng::GameRng,
+    terrain::Terrain,
+    tile::Tile,
+};
+
+/// Everything that shapes a generated map. The defaults are what the game
+/// uses, and any of them can be changed with a JSON file passed to `--map`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MapParams {
+    pub width: i32,
+    pub height: i32,
+    /// How many towns there are, each a city centre ringed by suburbs.
+    pub towns: usize,
+    /// How far from its centre a town's city blocks reach.
+    pub city_radius: i32,
+    /// How far from its centre a town's suburbs reach.
+    pub suburb_radius: i32,
+    pub forests: usize,
+    /// The largest a forest can be, from its centre.
+    pub forest_radius: i32,
+    /// How many rivers run from the top of the map to the bottom.
+    pub rivers: usize,
+    /// The chance of a river bending sideways at each step.
+    pub river_bend_chance: f64,
+    /// How many bridges cross each river, besides those carrying highways and
+    /// town streets.
+    pub bridges: usize,
+    /// Whether highways link each town to the next.
+    pub highways: bool,
+    /// The chance of a tile of each terrain holding a location, in the order
+    /// of [`Terrain::ALL`].
+    pub location_chances: [f64; 6],
+    /// A hash of the file the parameters were read from.
+    #[serde(skip)]
+    hash: u64,
+}
+
+impl Default for MapParams {
+    fn default() -> Self {
+        Self {
+            width: 60,
+            height: 30,
+            towns: 4,
+            city_radius: 2,
+            suburb_radius: 5,
+            forests: 6,
+            forest_radius: 5,
+            rivers: 2,
+            river_bend_chance: 0.4,
+            bridges: 2,
+            highways: true,
+            location_chances: [0.3, 0.18, 0.08, 0.08, 0.0, 0.05],
+            hash: EMPTY_HASH,
+        }
+    }
+}
+
+impl MapParams {
+    /// Reads the parameters from `path`, or the defaults if there is none.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let json =
+            read_to_string(path).map_err(|error| format!("it could not be read ({error})"))?;
+        let mut params: Self =
+            serde_json::from_str(&json).map_err(|error| format!("it is not valid ({error})"))?;
+        params.validate()?;
+        params.hash = content::hash(EMPTY_HASH, &json);
+        Ok(params)
+    }
+
+    /// Fails on parameters the generator cannot make a map from.
+    fn validate(&self) -> Result<(), String> {
+        if self.width < 1 || self.height < 1 {
+            return Err("it needs a `width` and `height` of at least 1".to_owned());
+        }
+        // Each river covers at most two tiles of every row, so this much of
+        // the map is always dry.
+        let dry_width = i64::from(self.width) - 2 * self.rivers as i64;
+        if dry_width < 1 {
+            return Err(
+                "it needs a `width` of more than twice its `rivers`, to leave dry land".to_owned(),
+            );
+        }
+        if dry_width * i64::from(self.height) <= LANDMARKS as i64 {
+            return Err(
+                "it needs more dry land than its `rivers` leave, to place the campaign's landmarks"
+                    .to_owned(),
+            );
+        }
+        // Tiles wrap round, so nothing is further than half the map away.
+        if self.width / 2 + self.height / 2 < MIN_DISTANCE {
+            return Err(format!(
+                "it needs to be large enough for the campaign's landmarks to be {MIN_DISTANCE} tiles from the start"
+            ));
+        }
+        if self.city_radius < 0 || self.suburb_radius < self.city_radius {
+            return Err(
+                "it needs a `suburb_radius` at least as large as its `city_radius`".to_owned(),
+            );
+        }
+        if self.forest_radius < 0 {
+            return Err("it has a negative `forest_radius`".to_owned());
+        }
+        if !(0.0..=1.0).contains(&self.river_bend_chance)
+            || !self
+                .location_chances
+                .iter()
+                .all(|chance| (0.0..=1.0).contains(chance))
+        {
+            return Err("it has a chance that is not between 0 and 1".to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    fn location_chance(&self, terrain: Terrain) -> f64 {
+        let index = Terrain::ALL
+            .iter()
+            .position(|candidate| *candidate == terrain)
+            .expect("terrain to be listed");
+        self.location_chances[index]
+    }
+}
+
+/// Lays out the terrain in coherent regions, then scatters locations over
+/// the terrain that suits them. Rows run from north to south.
+pub fn generate(params: &MapParams, locations: &Locations, rng: &mut GameRng) -> Vec<Vec<Tile>> {
+    let (width, height) = (params.width, params.height);
+    let mut terrain = vec![vec![Terrain::Farmland; width as usize]; height as usize];
+    let mut roads = vec![vec![false; width as usize]; height as usize];
+    let wrap = |x: i32, y: i32| (x.rem_euclid(width) as usize, y.rem_euclid(height) as usize);
+    let mut set = |x: i32, y: i32, value: Terrain| {
+        let (x, y) = wrap(x, y);
+        terrain[y][x] = value;
+    };
+
+    // Forests are rough blobs, with their edges left ragged.
+    for _ in 0..params.forests {
+        let (cx, cy) = (rng.gen_range(0..width), rng.gen_range(0..height));
+        let radius = rng.gen_range(2..=params.forest_radius.max(2));
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let distance = dx.abs() + dy.abs();
+                if distance < radius || (distance == radius && rng.gen_bool(0.5)) {
+                    set(cx + dx, cy + dy, Terrain::Forest);
+                }
+            }
+        }
+    }
+
+    // Towns go down over the top, so the odd forest ends at the edge of town.
+    let mut towns = Vec::new();
+    for _ in 0..params.towns {
+        let (cx, cy) = (rng.gen_range(0..width), rng.gen_range(0..height));
+        for dy in -params.suburb_radius..=params.suburb_radius {
+            for dx in -params.suburb_radius..=params.suburb_radius {
+                let distance = dx.abs().max(dy.abs());
+                if distance <= params.city_radius {
+                    set(cx + dx, cy + dy, Terrain::City);
+                } else if dx.abs() + dy.abs() <= params.suburb_radius + 1 {
+                    set(cx + dx, cy + dy, Terrain::Suburbs);
+                }
+            }
+        }
+        towns.push((cx, cy));
+    }
+
+    // Rivers wander from the top of the map to the bottom, with a few bridges
+    // carrying country roads over them.
+    let mut crossings = Vec::new();
+    for _ in 0..params.rivers {
+        let mut x = rng.gen_range(0..width);
+        let mut banks = Vec::new();
+        for y in 0..height {
+            set(x, y, Terrain::River);
+            let start = x;
+            if rng.gen_bool(params.river_bend_chance) {
+                x += if rng.gen_bool(0.5) { 1 } else { -1 };
+                set(x, y, Terrain::River);
+            }
+            banks.push((start.min(x), start.max(x), y));
+        }
+        crossings.extend(banks.choose_multiple(rng, params.bridges).copied());
+    }
+    for (west, east, y) in crossings {
+        for x in west..=east {
+            let (x, y) = wrap(x, y);
+            roads[y][x] = true;
+        }
+    }
+
+    // Streets cross each town from one edge of its suburbs to the other,
+    // bridging any river running through it.
+    for &(cx, cy) in towns.iter() {
+        for offset in -params.suburb_radius..=params.suburb_radius {
+            for (x, y) in [(cx + offset, cy), (cx, cy + offset)] {
+                let (x, y) = wrap(x, y);
+                roads[y][x] = true;
+            }
+        }
+    }
+
+    // Highways run east and then north or south between towns, bridging any
+    // rivers along the way.
+    if params.highways && towns.len() > 1 {
+        let mut highway = |x: i32, y: i32| {
+            let (x, y) = wrap(x, y);
+            terrain[y][x] = Terrain::Highway;
+            roads[y][x] = true;
+        };
+        for pair in towns.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+            let mut x = x1;
+            while x != x2 {
+                highway(x, y1);
+                x += (x2 - x1).signum();
+            }
+            let mut y = y1;
+            while y != y2 {
+                highway(x2, y);
+                y += (y2 - y1).signum();
+            }
+        }
+    }
+
+    bridge_cut_off_land(&terrain, &mut roads);
+
+    terrain
+        .into_iter()
+        .zip(roads)
+        .map(|(row, roads)| {
+            row.into_iter()
+                .zip(roads)
+                .map(|(terrain, road)| {
+                    let location = if rng.gen_bool(params.location_chance(terrain)) {
+                        locations.random(terrain, rng)
+                    } else {
+                        None
+                    };
+                    Tile::new(terrain, road, location)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Bridges rivers until every tile off them can be driven to, as rivers
+/// running side by side can trap land between them with no way across.
+fn bridge_cut_off_land(terrain: &[Vec<Terrain>], roads: &mut [Vec<bool>]) {
+    let (width, height) = (terrain[0].len(), terrain.len());
+    let neighbours = |(x, y): (usize, usize)| {
+        [
+            ((x + 1) % width, y),
+            ((x + width - 1) % width, y),
+            (x, (y + 1) % height),
+            (x, (y + height - 1) % height),
+        ]
+    };
+
+    loop {
+        // Number each stretch of drivable land, and find the largest.
+        let drivable = |roads: &[Vec<bool>], (x, y): (usize, usize)| {
+            roads[y][x] || terrain[y][x] != Terrain::River
+        };
+        let mut regions = vec![vec![None; width]; height];
+        let mut sizes = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if regions[y][x].is_some() || !drivable(roads, (x, y)) {
+                    continue;
+                }
+                let region = sizes.len();
+                regions[y][x] = Some(region);
+                let mut queue = VecDeque::from([(x, y)]);
+                let mut size = 0;
+                while let Some(tile) = queue.pop_front() {
+                    size += 1;
+                    for (x, y) in neighbours(tile) {
+                        if regions[y][x].is_none() && drivable(roads, (x, y)) {
+                            regions[y][x] = Some(region);
+                            queue.push_back((x, y));
+                        }
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        let Some(main) = (0..sizes.len()).max_by_key(|region| sizes[*region]) else {
+            return;
+        };
+        let Some(start) = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .find(|&(x, y)| regions[y][x].is_some_and(|region| region != main))
+        else {
+            return;
+        };
+
+        // Head for the nearest land in the largest stretch, bridging every
+        // river on the way.
+        let mut came_from = vec![vec![None; width]; height];
+        came_from[start.1][start.0] = Some(start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(tile) = queue.pop_front() {
+            if regions[tile.1][tile.0] == Some(main) {
+                let mut step = tile;
+                while step != start {
+                    let (x, y) = step;
+                    if terrain[y][x] == Terrain::River {
+                        roads[y][x] = true;
+                    }
+                    step = came_from[y][x].expect("route to lead back to the start");
+                }
+                break;
+            }
+            for (x, y) in neighbours(tile) {
+                if came_from[y][x].is_none() {
+                    came_from[y][x] = Some(tile);
+                    queue.push_back((x, y));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+
+    fn terrain_counts(params: &MapParams, seed: u64) -> Vec<usize> {
+        let rows = generate(params, &Locations::built_in(), &mut rng::seeded(seed));
+        assert_eq!(rows.len(), params.height as usize);
+        assert!(rows.iter().all(|row| row.len() == params.width as usize));
+        Terrain::ALL
+            .iter()
+            .map(|terrain| {
+                rows.iter()
+                    .flatten()
+                    .filter(|tile| tile.terrain == *terrain)
+                    .count()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_map() {
+        let params = MapParams::default();
+        let map = |seed| {
+            let rows = generate(&params, &Locations::built_in(), &mut rng::seeded(seed));
+            serde_json::to_string(&rows).expect("map to serialise")
+        };
+        assert_eq!(map(7), map(7));
+        assert_ne!(map(7), map(8));
+    }
+
+    #[test]
+    fn terrain_stays_within_the_requested_proportions() {
+        let params = MapParams::default();
+        let tiles = (params.width * params.height) as usize;
+        let town = (2 * params.suburb_radius + 1).pow(2) as usize;
+        let city = (2 * params.city_radius + 1).pow(2) as usize;
+        let forest = (2 * params.forest_radius.pow(2) + 2 * params.forest_radius + 1) as usize;
+        let highway = (params.width + params.height) as usize;
+        // The most tiles each terrain can cover, in the order of `Terrain::ALL`.
+        let most = [
+            params.towns * city,
+            params.towns * town,
+            params.forests * forest,
+            tiles,
+            params.rivers * 2 * params.height as usize,
+            (params.towns - 1) * highway,
+        ];
+
+        for seed in 0..20 {
+            let counts = terrain_counts(&params, seed);
+            for ((terrain, count), most) in Terrain::ALL.iter().zip(&counts).zip(most) {
+                assert!(*count > 0, "seed {seed} has no {terrain:?}");
+                assert!(*count <= most, "seed {seed} has {count} {terrain:?}");
+            }
+            assert!(counts[3] * 2 >= tiles, "seed {seed} is mostly built over");
+        }
+    }
+
+    #[test]
+    fn params_shape_the_map() {
+        let params = MapParams {
+            width: 20,
+            height: 10,
+            forests: 0,
+            rivers: 0,
+            highways: false,
+            ..MapParams::default()
+        };
+        let counts = terrain_counts(&params, 3);
+        assert_eq!(counts[2], 0);
+        assert_eq!(counts[4], 0);
+        assert_eq!(counts[5], 0);
+    }
+
+    #[test]
+    fn load_rejects_params_it_cannot_build_from() {
+        let params: MapParams = serde_json::from_str(r#"{"width": 0}"#).expect("params to parse");
+        assert!(params.validate().is_err());
+        let params: MapParams =
+            serde_json::from_str(r#"{"river_bend_chance": 1.5}"#).expect("params to parse");
+        assert!(params.validate().is_err());
+        assert!(serde_json::from_str::<MapParams>(r#"{"size": 10}"#).is_err());
+        for json in [
+            r#"{"width": 8, "height": 8}"#,
+            r#"{"width": 1, "height": 1, "rivers": 1}"#,
+            r#"{"width": 3, "height": 1, "rivers": 0, "towns": 0, "forests": 0}"#,
+            r#"{"width": 4, "height": 40, "rivers": 2}"#,
+        ] {
+            let params: MapParams = serde_json::from_str(json).expect("params to parse");
+            assert!(params.validate().is_err(), "{json}");
+        }
+        assert!(MapParams::default().validate().is_ok());
+    }
+}
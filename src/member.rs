@@ -3,15 +3,48 @@ use std::{
     fmt::Display,
 };
 
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 
-use crate::io::ellipsis;
+use crate::{
+    io::Frontend,
+    rng::GameRng,
+    traits::{Trait, GLUTTON_MEAL, MARKSMAN_DAMAGE},
+    weapon::{Weapon, FISTS_DAMAGE},
+};
 
 const REANIMATION_THRESHOLD: i32 = 20;
-const INFECTION_DAMAGE_THRESHOLD: i32 = 25;
+pub const INFECTION_DAMAGE_THRESHOLD: i32 = 25;
 const INFECTION_DAMAGE: i32 = 4;
 
+/// How full a member is after a meal. Every meal time takes away
+/// [`HUNGER`], and members with none left start starving, three days after
+/// their last meal.
+pub const SATIATED: i32 = 9;
+pub const HUNGER: i32 = 1;
+
+/// How high morale can go, and where everyone starts.
+pub const MAX_MORALE: i32 = 10;
+const START_MORALE: i32 = 7;
+/// Members at or below this morale may panic, refuse orders and desert once
+/// it hits zero.
+const SHAKEN_MORALE: i32 = 3;
+/// Members at or above this morale fight harder.
+const INSPIRED_MORALE: i32 = 8;
+/// How much harder an inspired member hits.
+const INSPIRED_DAMAGE: i32 = 1;
+
+/// Experience for each zombie a member neutralises.
+pub const KILL_XP: i32 = 2;
+/// Experience for each member after a search the party survives.
+pub const SEARCH_XP: i32 = 1;
+/// Experience for each member for every day the party survives.
+pub const DAY_XP: i32 = 3;
+/// The experience needed to go from level `n` to `n + 1` is `n` times this.
+const LEVEL_XP: i32 = 10;
+/// How much maximum health a level up can add.
+const LEVEL_HP: i32 = 3;
+
 pub enum DeathCheckResult {
     Alive,
     Dead,
@@ -62,29 +95,92 @@ impl NamePool {
         }
     }
 
-    pub fn get(&mut self) -> String {
-        self.available.shuffle(&mut thread_rng());
+    pub fn get(&mut self, rng: &mut GameRng) -> String {
+        self.available.shuffle(rng);
         match self.available.pop() {
             Some(name) => name,
-            None => NamePool::new().get(),
+            None => NamePool::new().get(rng),
         }
     }
 }
 
+impl Default for NamePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Member {
     pub name: String,
     pub hp: i32,
     pub max_hp: i32,
     pub infection_level: i32,
+    #[serde(default)]
+    pub weapon: Option<Weapon>,
+    #[serde(default)]
+    pub traits: Vec<Trait>,
+    #[serde(default = "first_level")]
+    pub level: i32,
+    /// Experience towards the next level.
+    #[serde(default)]
+    pub xp: i32,
+    /// Taken off the infection gained from every hit.
+    #[serde(default)]
+    pub infection_resistance: i32,
+    #[serde(default = "satiated")]
+    pub satiation: i32,
+    #[serde(default = "start_morale")]
+    pub morale: i32,
+}
+
+fn first_level() -> i32 {
+    1
+}
+
+fn satiated() -> i32 {
+    SATIATED
+}
+
+fn start_morale() -> i32 {
+    START_MORALE
 }
 
 impl Display for Member {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}:\n- HP: {}/{}\n- Infection: {}",
-            self.name, self.hp, self.max_hp, self.infection_level
+            "{}:\n- Level: {} ({}/{} XP)\n- HP: {}/{}\n- Satiation: {}/{SATIATED}\n- Morale: {}/{MAX_MORALE} ({})\n- Infection: {} (resistance {})\n- Weapon: {}\n- Traits: {}",
+            self.name,
+            self.level,
+            self.xp,
+            self.next_level_xp(),
+            self.hp,
+            self.max_hp,
+            self.satiation,
+            self.morale,
+            if self.shaken() {
+                "shaken"
+            } else if self.inspired() {
+                "inspired"
+            } else {
+                "steady"
+            },
+            self.infection_level,
+            self.infection_resistance,
+            match &self.weapon {
+                Some(weapon) => weapon.to_string(),
+                None => "None".to_owned(),
+            },
+            if self.traits.is_empty() {
+                "None".to_owned()
+            } else {
+                self.traits
+                    .iter()
+                    .map(Trait::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
         )
     }
 }
@@ -99,27 +195,185 @@ impl Member {
             },
             max_hp,
             infection_level,
+            weapon: None,
+            traits: Vec::new(),
+            level: first_level(),
+            xp: 0,
+            infection_resistance: 0,
+            satiation: SATIATED,
+            morale: START_MORALE,
+        }
+    }
+
+    /// A survivor met along the way, who brings one trait with them.
+    pub fn survivor(
+        name: &str,
+        max_hp: i32,
+        infection_level: i32,
+        current_hp: i32,
+        rng: &mut GameRng,
+    ) -> Self {
+        Self {
+            traits: vec![Trait::random(rng)],
+            ..Self::new(name, max_hp, infection_level, Some(current_hp))
         }
     }
 
     pub fn velma() -> Self {
-        Self::new("Velma", 17, 3, None)
+        Self {
+            traits: vec![Trait::Medic],
+            ..Self::new("Velma", 17, 3, None)
+        }
     }
 
     pub fn shaggy() -> Self {
-        Self::new("Shaggy", 12, 0, None)
+        Self {
+            traits: vec![Trait::Glutton, Trait::Runner],
+            ..Self::new("Shaggy", 12, 0, None)
+        }
     }
 
     pub fn fred() -> Self {
-        Self::new("Fred", 20, 7, None)
+        Self {
+            traits: vec![Trait::Mechanic],
+            ..Self::new("Fred", 20, 7, None)
+        }
     }
 
     pub fn daphne() -> Self {
-        Self::new("Daphne", 15, 1, None)
+        Self {
+            traits: vec![Trait::Marksman],
+            ..Self::new("Daphne", 15, 1, None)
+        }
     }
 
     pub fn scoob() -> Self {
-        Self::new("Scoob", 15, 0, None)
+        Self {
+            traits: vec![Trait::Glutton, Trait::Scout],
+            ..Self::new("Scoob", 15, 0, None)
+        }
+    }
+
+    /// One of the gang, by name.
+    pub fn gang(name: &str) -> Option<Self> {
+        match name {
+            "Velma" => Some(Self::velma()),
+            "Shaggy" => Some(Self::shaggy()),
+            "Fred" => Some(Self::fred()),
+            "Daphne" => Some(Self::daphne()),
+            "Scoob" => Some(Self::scoob()),
+            _ => None,
+        }
+    }
+
+    pub fn has(&self, member_trait: Trait) -> bool {
+        self.traits.contains(&member_trait)
+    }
+
+    /// How much the member eats in one go: whole food when fed by hand, or
+    /// portions of it at meal times.
+    pub fn meal(&self) -> i32 {
+        if self.has(Trait::Glutton) {
+            GLUTTON_MEAL
+        } else {
+            1
+        }
+    }
+
+    /// The damage of a shot from the member's gun, if they have one.
+    pub fn gun_damage(&self) -> Option<i32> {
+        let stats = self.weapon.as_ref()?.stats();
+        stats.ammo?;
+        let bonus = if self.has(Trait::Marksman) {
+            MARKSMAN_DAMAGE
+        } else {
+            0
+        };
+        Some(stats.damage + bonus + self.morale_damage())
+    }
+
+    /// The damage of a melee hit, with bare hands if the member's weapon is a
+    /// gun.
+    pub fn melee_damage(&self) -> i32 {
+        let damage = match &self.weapon {
+            Some(weapon) if weapon.stats().ammo.is_none() => weapon.stats().damage,
+            _ => FISTS_DAMAGE,
+        };
+        damage + self.morale_damage()
+    }
+
+    fn morale_damage(&self) -> i32 {
+        if self.inspired() {
+            INSPIRED_DAMAGE
+        } else {
+            0
+        }
+    }
+
+    pub fn change_morale(&mut self, amount: i32) {
+        self.morale = (self.morale + amount).clamp(0, MAX_MORALE);
+    }
+
+    pub fn shaken(&self) -> bool {
+        self.morale <= SHAKEN_MORALE
+    }
+
+    /// Whether the member has given up, and may leave the party.
+    pub fn broken(&self) -> bool {
+        self.morale == 0
+    }
+
+    pub fn inspired(&self) -> bool {
+        self.morale >= INSPIRED_MORALE
+    }
+
+    /// Wears down the member's weapon after using it, throwing it away if it
+    /// breaks.
+    pub fn wear_weapon(&mut self, io: &mut dyn Frontend) {
+        if let Some(weapon) = &mut self.weapon {
+            if weapon.wear() {
+                io.say(&format!(
+                    "{}'s {} breaks, and is tossed aside.",
+                    self.name, weapon.kind
+                ));
+                self.weapon = None;
+            }
+        }
+    }
+
+    fn next_level_xp(&self) -> i32 {
+        LEVEL_XP * self.level
+    }
+
+    /// Adds experience, levelling up as many times as it allows. Each level
+    /// raises maximum health, infection resistance, or teaches a new trait.
+    pub fn gain_xp(&mut self, amount: i32, rng: &mut GameRng, io: &mut dyn Frontend) {
+        self.xp += amount;
+        while self.xp >= self.next_level_xp() {
+            self.xp -= self.next_level_xp();
+            self.level += 1;
+            io.say(&format!("{} has reached level {}!", self.name, self.level));
+
+            let learned = match rng.gen_range(0..3) {
+                0 => Trait::learn(&self.traits, rng),
+                _ => None,
+            };
+            if let Some(learned) = learned {
+                self.traits.push(learned);
+                io.say(&format!("{} is now a {learned}.", self.name));
+            } else if rng.gen_bool(0.5) {
+                self.infection_resistance += 1;
+                io.say(&format!(
+                    "{} feels a little more resistant to the infection.",
+                    self.name
+                ));
+            } else {
+                self.max_hp += LEVEL_HP;
+                self.hp += LEVEL_HP;
+                io.say(&format!("+{LEVEL_HP} max HP for {}", self.name));
+            }
+            io.pause();
+        }
     }
 
     pub fn check_dead(&self) -> DeathCheckResult {
@@ -144,33 +398,33 @@ impl Member {
         self.infection_level = max(self.infection_level, 0);
     }
 
-    pub fn hurt(&mut self, damage: i32) {
+    pub fn hurt(&mut self, damage: i32, io: &mut dyn Frontend) {
         if damage >= 6 {
-            println!(
+            io.say(&format!(
                 "{} takes {} damage, and becomes much more infected...",
                 self.name, damage
-            );
-            self.infection_level += 5;
+            ));
+            self.infection_level += max(0, 5 - self.infection_resistance);
         } else {
-            println!(
+            io.say(&format!(
                 "{} takes {} damage, and becomes slightly more infected...",
                 self.name, damage
-            );
-            self.infection_level += 2;
+            ));
+            self.infection_level += max(0, 2 - self.infection_resistance);
         }
 
         self.hp -= min(self.hp, damage);
     }
 
-    pub fn check_infection(&mut self) -> DeathCheckResult {
+    pub fn check_infection(&mut self, io: &mut dyn Frontend) -> DeathCheckResult {
         if self.infection_level >= INFECTION_DAMAGE_THRESHOLD {
             self.hp -= INFECTION_DAMAGE;
-            print!(
+            io.write(&format!(
                 "{} coughs violently and takes {INFECTION_DAMAGE} damage",
                 self.name
-            );
-            ellipsis();
-            println!();
+            ));
+            io.ellipsis();
+            io.say("");
         }
         self.check_dead()
     }
@@ -0,0 +1,263 @@
+use std::fmt::Display;
+
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::{
+    member::Member,
+    rng,
+    weapon::{Weapon, WeaponKind},
+};
+
+/// The save format version written by this build of the game.
+pub const CURRENT_VERSION: u32 = 3;
+
+/// Upgrades the game JSON of one version to the next. The migration at index
+/// `n` turns a version `n` save into a version `n + 1` save.
+type Migration = fn(&mut JsonMap<String, Value>) -> Result<(), String>;
+
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2, v2_to_v3];
+
+pub enum MigrationError {
+    /// The save was written by a newer build of the game.
+    TooNew(u32),
+    /// The save is not laid out the way its version says it should be.
+    Malformed { version: u32, reason: String },
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::TooNew(version) => write!(
+                f,
+                "it was made by a newer version of the game (save format {version}, this build reads up to {CURRENT_VERSION})"
+            ),
+            MigrationError::Malformed { version, reason } => {
+                write!(f, "it could not be upgraded from save format {version}: {reason}")
+            }
+        }
+    }
+}
+
+/// Splits a save file into its version and game data, then runs every
+/// migration needed to bring the game data up to [`CURRENT_VERSION`].
+///
+/// Saves written before the header existed are treated as version 0.
+pub fn upgrade(save: Value) -> Result<Value, MigrationError> {
+    let (version, game) = match save {
+        Value::Object(mut header) if header.contains_key("version") => {
+            let version = header
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| MigrationError::Malformed {
+                    version: 0,
+                    reason: "the version is not a number".to_owned(),
+                })?;
+            let game = header
+                .remove("game")
+                .ok_or_else(|| MigrationError::Malformed {
+                    version,
+                    reason: "there is no game data".to_owned(),
+                })?;
+            (version, game)
+        }
+        game => (0, game),
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(MigrationError::TooNew(version));
+    }
+
+    let Value::Object(mut game) = game else {
+        return Err(MigrationError::Malformed {
+            version,
+            reason: "the game data is not an object".to_owned(),
+        });
+    };
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut game).map_err(|reason| MigrationError::Malformed {
+            version: from as u32,
+            reason,
+        })?;
+    }
+
+    Ok(Value::Object(game))
+}
+
+/// Headerless saves may predate the seeded RNG and the chosen character being
+/// recorded. Missing seeds get a fresh one, and the character is recovered from
+/// the party if one of the gang is still alive.
+fn v0_to_v1(game: &mut JsonMap<String, Value>) -> Result<(), String> {
+    if !game.contains_key("seed") || !game.contains_key("rng") {
+        let seed = rng::random_seed();
+        // The RNG state contains `u128`s, which only survive a round trip
+        // through text rather than `serde_json::to_value`.
+        let rng = serde_json::to_string(&rng::seeded(seed))
+            .and_then(|json| serde_json::from_str(&json))
+            .map_err(|error| error.to_string())?;
+        game.insert("seed".to_owned(), json!(seed));
+        game.insert("rng".to_owned(), rng);
+    }
+
+    if !game.contains_key("character") {
+        let members = game
+            .get("party")
+            .and_then(|party| party.get("members"))
+            .and_then(Value::as_array)
+            .ok_or("the party has no members list")?;
+        let character = members
+            .iter()
+            .filter_map(|member| member.get("name").and_then(Value::as_str))
+            .find(|name| ["Velma", "Shaggy", "Fred", "Daphne"].contains(name))
+            .unwrap_or("Unknown");
+        game.insert("character".to_owned(), json!(character));
+    }
+
+    Ok(())
+}
+
+/// The shotgun used to belong to the whole party. It goes to the character
+/// being played if they are still alive, or else whoever leads the party.
+fn v1_to_v2(game: &mut JsonMap<String, Value>) -> Result<(), String> {
+    let character = game
+        .get("character")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let members = game
+        .get_mut("party")
+        .and_then(|party| party.get_mut("members"))
+        .and_then(Value::as_array_mut)
+        .ok_or("the party has no members list")?;
+
+    let index = members
+        .iter()
+        .position(|member| member.get("name").and_then(Value::as_str) == Some(&character))
+        .unwrap_or(0);
+    if let Some(Value::Object(member)) = members.get_mut(index) {
+        let shotgun = serde_json::to_value(Weapon::new(WeaponKind::Shotgun))
+            .map_err(|error| error.to_string())?;
+        member.insert("weapon".to_owned(), shotgun);
+    }
+
+    Ok(())
+}
+
+/// Gives the gang the traits they now start with. Survivors recruited before
+/// traits existed are left without any.
+fn v2_to_v3(game: &mut JsonMap<String, Value>) -> Result<(), String> {
+    let members = game
+        .get_mut("party")
+        .and_then(|party| party.get_mut("members"))
+        .and_then(Value::as_array_mut)
+        .ok_or("the party has no members list")?;
+
+    for member in members.iter_mut() {
+        let gang = member
+            .get("name")
+            .and_then(Value::as_str)
+            .and_then(Member::gang);
+        if let (Some(gang), Value::Object(member)) = (gang, member) {
+            let traits = serde_json::to_value(gang.traits).map_err(|error| error.to_string())?;
+            member.insert("traits".to_owned(), traits);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Game, rng::GameRng, traits::Trait};
+
+    /// A save as the original release wrote it to `savegame.json`, with no
+    /// header, seed or character, and a party that has recruited a survivor.
+    const BASELINE_SAVE: &str = r#"{"party":{"ammo":4,"money":5,"fuel":2,"medicine":1,"food":3,"members":[{"name":"Shaggy","hp":12,"max_hp":15,"infection_level":2},{"name":"Scoob","hp":15,"max_hp":15,"infection_level":0},{"name":"Luna","hp":8,"max_hp":10,"infection_level":1}]},"map":{"position":{"x":1,"y":0},"width":2,"height":1,"rows":[[{"seen":true,"explored":true,"location_type":null},{"seen":true,"explored":false,"location_type":null}]]},"time":{"hour":14,"day":3},"name_pool":{"available":["Scrappy-Doo","Dusk"]}}"#;
+
+    fn upgraded(save: &str) -> Value {
+        let save = serde_json::from_str(save).expect("fixture to be valid JSON");
+        match upgrade(save) {
+            Ok(game) => game,
+            Err(error) => panic!("save to upgrade, but {error}"),
+        }
+    }
+
+    fn member<'a>(game: &'a Value, name: &str) -> &'a Value {
+        game["party"]["members"]
+            .as_array()
+            .expect("members to be a list")
+            .iter()
+            .find(|member| member["name"] == name)
+            .expect("member to be in the party")
+    }
+
+    #[test]
+    fn baseline_save_is_seeded() {
+        let game = upgraded(BASELINE_SAVE);
+
+        let seed = game["seed"].as_u64().expect("seed to be a number");
+        let rng: GameRng = serde_json::from_str(&game["rng"].to_string()).expect("rng to load");
+        let fresh = serde_json::to_string(&rng::seeded(seed)).expect("serialize to succeed");
+        assert_eq!(
+            serde_json::to_string(&rng).expect("serialize to succeed"),
+            fresh
+        );
+    }
+
+    #[test]
+    fn baseline_save_recovers_the_character() {
+        let game = upgraded(BASELINE_SAVE);
+        assert_eq!(game["character"], "Shaggy");
+    }
+
+    #[test]
+    fn baseline_save_gives_the_character_the_shotgun() {
+        let game = upgraded(BASELINE_SAVE);
+        let shotgun = serde_json::to_value(Weapon::new(WeaponKind::Shotgun)).expect("serialize");
+
+        assert_eq!(member(&game, "Shaggy")["weapon"], shotgun);
+        assert!(member(&game, "Scoob").get("weapon").is_none());
+        assert!(member(&game, "Luna").get("weapon").is_none());
+    }
+
+    #[test]
+    fn baseline_save_gives_the_gang_their_traits() {
+        let game = upgraded(BASELINE_SAVE);
+        let traits = |name| -> Vec<Trait> {
+            serde_json::from_value(member(&game, name)["traits"].clone()).expect("traits to load")
+        };
+
+        assert_eq!(traits("Shaggy"), Member::shaggy().traits);
+        assert_eq!(traits("Scoob"), Member::scoob().traits);
+        assert!(member(&game, "Luna").get("traits").is_none());
+    }
+
+    #[test]
+    fn baseline_save_loads_as_a_game() {
+        let game: Game = serde_json::from_value(upgraded(BASELINE_SAVE)).expect("game to load");
+        assert_eq!(game.party().members.len(), 3);
+        assert_eq!(game.time().day(), 3);
+    }
+
+    #[test]
+    fn save_without_the_gang_gives_the_leader_the_shotgun() {
+        let save = r#"{"party":{"ammo":0,"money":0,"fuel":0,"medicine":0,"food":0,"members":[{"name":"Luna","hp":8,"max_hp":10,"infection_level":1},{"name":"Dusk","hp":5,"max_hp":10,"infection_level":0}]},"map":{"position":{"x":0,"y":0},"width":1,"height":1,"rows":[[{"seen":true,"explored":false,"location_type":null}]]},"time":{"hour":8,"day":9},"name_pool":{"available":[]}}"#;
+        let game = upgraded(save);
+        let shotgun = serde_json::to_value(Weapon::new(WeaponKind::Shotgun)).expect("serialize");
+
+        assert_eq!(game["character"], "Unknown");
+        assert_eq!(member(&game, "Luna")["weapon"], shotgun);
+        assert!(member(&game, "Dusk").get("weapon").is_none());
+    }
+
+    #[test]
+    fn newer_save_is_rejected() {
+        let save = json!({ "version": CURRENT_VERSION + 1, "game": {} });
+        assert!(matches!(
+            upgrade(save),
+            Err(MigrationError::TooNew(version)) if version == CURRENT_VERSION + 1
+        ));
+    }
+}
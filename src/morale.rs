@@ -0,0 +1,110 @@
+use rand::Rng;
+
+use crate::{
+    io::Frontend,
+    party::{Casualty, Cause, Party},
+    relationships,
+    rng::GameRng,
+};
+
+/// How much losing a companion shakes everyone else.
+const GRIEF: i32 = 2;
+/// How much worse it is to watch a companion get back up.
+const HORROR: i32 = 2;
+/// How much a new face lifts everyone's spirits.
+pub const RECRUIT_MORALE: i32 = 2;
+/// How much a proper meal lifts a member's spirits.
+pub const MEAL_MORALE: i32 = 1;
+/// How much going hungry wears a member down at every meal time.
+pub const STARVING_MORALE: i32 = 1;
+/// How much a quiet night's sleep lifts a member's spirits.
+pub const REST_MORALE: i32 = 1;
+/// How much getting lost wears everyone down.
+pub const LOST_MORALE: i32 = 1;
+
+/// The chance of a shaken member freezing up instead of acting during a fight.
+pub const PANIC_CHANCE: f64 = 0.3;
+/// The chance of a shaken member refusing to do what they are asked.
+pub const REFUSAL_CHANCE: f64 = 0.5;
+/// The chance of a broken member slipping away from the party every day.
+const DESERT_CHANCE: f64 = 0.5;
+
+/// Lowers everyone's morale for each companion lost, and more so for those
+/// who rose again or were close to them. The lost are then forgotten.
+pub fn mourn(party: &mut Party, casualties: &[Casualty], io: &mut dyn Frontend) {
+    let mut loss = 0;
+    for casualty in casualties {
+        match casualty.cause {
+            Cause::Deserted => continue,
+            _ if casualty.risen => loss += GRIEF + HORROR,
+            _ => loss += GRIEF,
+        }
+    }
+    if loss > 0 && !party.members.is_empty() {
+        io.say("\nThe loss weighs heavily on the rest of the party.");
+        party.change_morale(-loss);
+        relationships::bereave(party, casualties, io);
+        for member in party.members.iter().filter(|member| member.shaken()) {
+            io.say(&format!("{} is badly shaken.", member.name));
+        }
+        io.wait();
+    }
+
+    for casualty in casualties {
+        party.relationships.forget(&casualty.name);
+    }
+}
+
+/// Gives every member whose morale has broken a chance to leave the party in
+/// the night, as long as someone else is left behind.
+pub fn desert(party: &mut Party, rng: &mut GameRng, io: &mut dyn Frontend) {
+    let mut deserted = false;
+    let mut index = 0;
+    while index < party.members.len() {
+        let member = &party.members[index];
+        if member.broken() && party.members.len() > 1 && rng.gen_bool(DESERT_CHANCE) {
+            let member = party.members.remove(index);
+            io.say(&format!(
+                "{} is gone by morning, and so is any hope of them coming back.",
+                member.name
+            ));
+            party.bury(&member, Cause::Deserted);
+            deserted = true;
+        } else {
+            index += 1;
+        }
+    }
+    if deserted {
+        io.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, rng, upkeep};
+
+    #[test]
+    fn starving_to_death_is_grieved_but_not_feared() {
+        let mut party = Party::create(&mut rng::seeded(1), &mut Scripted::new(["1"]));
+        party.members.truncate(2);
+        party.food = 0;
+        let starving = &mut party.members[1];
+        let name = starving.name.clone();
+        starving.hp = 1;
+        starving.satiation = 0;
+        starving.infection_level = 100;
+
+        upkeep::eat(&mut party, 1, &mut Scripted::default());
+        assert!(party.members.iter().all(|member| member.name != name));
+        assert!(!party.casualties[0].risen);
+
+        // Without the two being close, grief is all that is left.
+        let leader = party.members[0].name.clone();
+        party.relationships.change(&leader, &name, -10);
+        let morale = party.members[0].morale;
+        let casualties = std::mem::take(&mut party.casualties);
+        mourn(&mut party, &casualties, &mut Scripted::default());
+        assert_eq!(party.members[0].morale, morale - GRIEF);
+    }
+}
@@ -1,111 +1,319 @@
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::{max, min},
     fmt::Display,
 };
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
 use crate::{
+    camp,
+    campaign::{self, Landmark, CLUE_CHANCE},
+    combat::{self, Surroundings},
+    content::Content,
     event::Event,
-    io::{clear, ellipsis, flush, pause, read_line, wait},
+    io::{Frontend, Question, View},
     map::{Direction, Map},
-    member::{DeathCheckResult, Member, NamePool},
+    member::{DeathCheckResult, Member, NamePool, DAY_XP, KILL_XP, SATIATED, SEARCH_XP},
+    morale::{self, MEAL_MORALE, PANIC_CHANCE},
+    relationships::{self, Relationships, FEED_RESENTMENT},
+    rng::GameRng,
+    settings,
     time::Time,
+    traits::{Trait, MEDIC_CURE_BONUS, SCOUT_SIGHT},
+    travel,
+    upkeep::{self, Rations},
+    vehicle::{self, Vehicle},
+    weapon::{AmmoType, Weapon, WeaponKind, FISTS_DAMAGE, ZOMBIE_HP},
 };
 
-const TRAVEL_COST: i32 = 1;
-const TRAVEL_TIME: i32 = 6;
+/// The actions offered by the main menu, in the order they are listed.
+#[derive(Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Action {
+    ShowMembers,
+    ShowMap,
+    Explore,
+    Feed,
+    Cure,
+    Rations,
+    Camp,
+    Van,
+    Autopilot,
+    Settings,
+}
+
+impl Action {
+    fn label(self) -> &'static str {
+        match self {
+            Action::ShowMembers => "Show party members",
+            Action::ShowMap => "Show map",
+            Action::Explore => "Explore area",
+            Action::Feed => "Feed party",
+            Action::Cure => "Cure party",
+            Action::Rations => "Set rations",
+            Action::Camp => "Make camp",
+            Action::Van => "Work on the mystery machine",
+            Action::Autopilot => "Autopilot",
+            Action::Settings => "Settings",
+        }
+    }
+
+    /// The number typed to choose this action.
+    pub fn input(self) -> String {
+        let index = Action::iter()
+            .position(|action| action == self)
+            .expect("action to be listed");
+        (index + 1).to_string()
+    }
+
+    fn from_input(input: &str) -> Option<Self> {
+        let index = input.trim().parse::<usize>().ok()?.checked_sub(1)?;
+        Action::iter().nth(index)
+    }
+}
+
+/// How a party member was lost.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cause {
+    Zombies,
+    Infection,
+    LeftBehind,
+    Starvation,
+    Deserted,
+}
+
+impl Display for Cause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cause::Zombies => write!(f, "Zombies"),
+            Cause::Infection => write!(f, "Infection"),
+            Cause::LeftBehind => write!(f, "Left behind"),
+            Cause::Starvation => write!(f, "Starvation"),
+            Cause::Deserted => write!(f, "Deserted"),
+        }
+    }
+}
+
+pub struct Casualty {
+    pub name: String,
+    pub cause: Cause,
+    /// Whether they came back as one of the undead.
+    pub risen: bool,
+}
+
+/// How a turn chosen from the main menu ended.
+pub enum Outcome {
+    /// The party explored their tile and ran into something.
+    Explored(Encounter),
+    /// The player handed control over to the autopilot for this many turns.
+    Autopilot(usize),
+    /// Anything else, such as travelling or feeding the party, along with
+    /// whatever the party ran into on the road or at camp.
+    Other(Option<Encounter>),
+}
+
+/// What the party ran into while exploring a tile, travelling or camping.
+#[derive(Clone)]
+pub enum Encounter {
+    /// A location's scene, by name.
+    Location(String),
+    Event(Event),
+    /// One of the campaign's landmarks, which the party did not escape from.
+    Landmark(Landmark),
+    /// The party made it out, winning the campaign.
+    Escaped,
+    /// Zombies caught the party out in the open on foot.
+    Ambush,
+    /// Zombies found the party's camp in the night.
+    CampAttack,
+}
+
+impl Display for Encounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Encounter::Location(name) => write!(f, "{name}"),
+            Encounter::Event(event) => write!(f, "{event}"),
+            Encounter::Landmark(landmark) => write!(f, "{landmark}"),
+            Encounter::Escaped => write!(f, "Escaped"),
+            Encounter::Ambush => write!(f, "Ambush on foot"),
+            Encounter::CampAttack => write!(f, "Attack on the camp"),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Party {
+    /// Shotgun shells.
     pub ammo: i32,
+    /// Rounds for the pistol and rifle.
+    #[serde(default)]
+    pub bullets: i32,
     pub money: i32,
     pub fuel: i32,
     pub medicine: i32,
     pub food: i32,
+    /// Meals left over from the last food shared out at a meal time.
+    #[serde(default)]
+    pub portions: i32,
     pub members: Vec<Member>,
+    #[serde(default)]
+    pub rations: Rations,
+    #[serde(default)]
+    pub relationships: Relationships,
+    /// Salvaged parts for repairing and upgrading the mystery machine.
+    #[serde(default)]
+    pub scrap: i32,
+    #[serde(default)]
+    pub van: Vehicle,
+    /// Members who have died since these were last collected.
+    #[serde(skip)]
+    pub casualties: Vec<Casualty>,
 }
 
 impl Display for Party {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = match upkeep::days_of_food(self, self.rations) {
+            Some(1) => "1 day".to_owned(),
+            Some(days) => format!("{days} days"),
+            None => "not eating".to_owned(),
+        };
         write!(
             f,
-            "- Ammo: {}\n- Money: {}\n- Fuel: {}\n- Food: {}\n- Medicine: {}",
-            self.ammo, self.money, self.fuel, self.food, self.medicine
+            "- Ammo: {}\n- Bullets: {}\n- Money: {}\n- Fuel: {}/{}\n- Food: {} ({days}, {} rations)\n- Medicine: {}\n- Scrap: {}\n- Mystery machine: {}/{} storage used, condition {}",
+            self.ammo,
+            self.bullets,
+            self.money,
+            self.fuel,
+            self.van.tank(),
+            self.food,
+            self.rations,
+            self.medicine,
+            self.scrap,
+            self.food + self.medicine,
+            self.van.storage(),
+            self.van.condition
         )
     }
 }
 
 impl Party {
-    pub fn create() -> Self {
-        let mut rng = thread_rng();
+    pub fn create(rng: &mut GameRng, io: &mut dyn Frontend) -> Self {
         let money = rng.gen_range(4..=6);
         let ammo = rng.gen_range(4..=5);
 
-        let member_1 = Party::choose_character();
+        let mut member_1 = Party::choose_character(io);
+        member_1.weapon = Some(Weapon::new(WeaponKind::Shotgun));
         let member_2 = Member::scoob();
 
-        print!(
+        io.write(&format!(
             "{name} is able to locate the mystery machine",
             name = member_1.name
-        );
-        ellipsis();
-        println!("\n{name} is still inside.", name = member_2.name);
+        ));
+        io.ellipsis();
+        io.say(&format!("\n{name} is still inside.", name = member_2.name));
 
         let party = Self {
             ammo,
+            bullets: 0,
             money,
             food: 4,
+            portions: 0,
             medicine: 0,
             fuel: 2,
             members: vec![member_1, member_2],
+            rations: Rations::default(),
+            relationships: Relationships::default(),
+            scrap: 0,
+            van: Vehicle::default(),
+            casualties: Vec::new(),
         };
 
         for member in party.members.iter() {
-            println!("{}\n", member);
+            io.say(&format!("{}\n", member));
         }
-        wait();
+        io.wait();
 
         party
     }
 
-    pub fn normalise_values(&mut self) {
+    /// Keeps supplies from going negative and, while the party is `with_van`,
+    /// leaves behind whatever the mystery machine has no room for, telling the
+    /// player what was lost.
+    pub fn normalise_values(&mut self, with_van: bool, io: &mut dyn Frontend) {
         self.food = max(0, self.food);
         self.ammo = max(0, self.ammo);
+        self.bullets = max(0, self.bullets);
         self.medicine = max(0, self.medicine);
         self.money = max(0, self.money);
+        self.scrap = max(0, self.scrap);
+        self.fuel = max(0, self.fuel);
+        if !with_van {
+            return;
+        }
+
+        let fuel = max(0, self.fuel - self.van.tank());
+        if fuel > 0 {
+            self.fuel -= fuel;
+            io.say(&format!("-{fuel} fuel (the tank is full)"));
+        }
+
+        // Whatever does not fit in the mystery machine is left behind, food
+        // first, as it is the cheaper to come by.
+        let overflow = max(0, self.food + self.medicine - self.van.storage());
+        let food = min(overflow, self.food);
+        let medicine = overflow - food;
+        for (amount, name) in [(food, "food"), (medicine, "medicine")] {
+            if amount > 0 {
+                io.say(&format!(
+                    "-{amount} {name} (no room in the mystery machine)"
+                ));
+            }
+        }
+        self.medicine -= medicine;
+        self.food -= food;
     }
 
-    pub fn combat(&mut self, mut zombie_count: i32) {
-        let mut rng = thread_rng();
-        // Shuffle Member List
-        self.members.shuffle(&mut rng);
+    /// Lets the player choose between fighting move by move or auto-resolving.
+    /// Returns how the fight ended.
+    pub fn combat(
+        &mut self,
+        zombie_count: i32,
+        surroundings: &mut Surroundings,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> combat::Outcome {
+        combat::fight(self, zombie_count, surroundings, rng, io)
+    }
 
-        let used_ammo = min(rng.gen_range(0..=self.ammo), zombie_count);
+    pub fn auto_resolve(
+        &mut self,
+        mut zombie_count: i32,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) {
+        // Shuffle Member List
+        self.members.shuffle(rng);
 
-        if used_ammo == zombie_count {
-            if zombie_count > 1 {
-                println!("You quickly drew your shotgun and managed to kill all the zombies, you were lucky this time.");
-            } else {
-                println!("You quickly drew your shotgun and managed to kill the attacking zombie, you were lucky this time.");
+        // Everyone with a loaded gun gets a chance to fire before the zombies
+        // reach the party.
+        let mut fired = false;
+        for index in 0..self.members.len() {
+            if zombie_count == 0 {
+                break;
             }
-        } else if used_ammo > 0 {
-            if used_ammo > 1 {
-                println!("You quickly drew your shotgun and managed to kill {} zombies before they could attack.", used_ammo);
-            } else {
-                println!("You quickly drew your shotgun and managed to kill one zombie before it could attack.");
+            if self.loaded(&self.members[index]) {
+                zombie_count = self.volley(index, zombie_count, rng, io);
+                fired = true;
             }
-            pause();
-            println!("The rest lurch towards the party...");
-        } else if used_ammo == 0 && self.ammo > 0 {
-            println!("You had ammo to your disposal, but were not able to draw your weapons quick enough to attack the zombies...");
         }
-
-        zombie_count -= used_ammo;
-        self.ammo -= used_ammo;
+        if fired && zombie_count > 0 {
+            io.pause();
+            io.say("The rest lurch towards the party...");
+        }
 
         while zombie_count > 0 && !self.members.is_empty() {
-            wait();
+            io.wait();
             let mut member = self
                 .members
                 .pop()
@@ -115,221 +323,544 @@ impl Party {
             let damage = rng.gen_range(attacking..=(attacking * 2));
 
             if attacking > 1 {
-                print!("{} zombies attack {}", attacking, member.name);
+                io.write(&format!("{} zombies attack {}", attacking, member.name));
             } else {
-                print!("A zombie attacks {}", member.name);
+                io.write(&format!("A zombie attacks {}", member.name));
             }
-            ellipsis();
-            println!();
+            io.ellipsis();
+            io.say("");
 
-            member.hurt(damage);
+            if damage >= member.hp {
+                if let Some(index) = relationships::sacrifice(self, &member.name, rng) {
+                    io.say(&format!(
+                        "{} throws themself in front of {}!",
+                        self.members[index].name, member.name
+                    ));
+                    std::mem::swap(&mut member, &mut self.members[index]);
+                }
+            }
+            member.hurt(damage, io);
 
-            pause();
+            io.pause();
 
             match member.check_dead() {
+                DeathCheckResult::Alive if member.shaken() && rng.gen_bool(PANIC_CHANCE) => {
+                    io.say(&format!(
+                        "{} panics, and scrambles away instead of fighting back.",
+                        member.name
+                    ));
+                    self.members.insert(0, member);
+                }
                 DeathCheckResult::Alive => {
-                    let retaliation = rng.gen_range(1..=attacking);
+                    // Fists are as good as it used to get, and proper melee
+                    // weapons hit harder.
+                    let retaliation = min(
+                        attacking,
+                        rng.gen_range(1..=attacking) * member.melee_damage() / FISTS_DAMAGE,
+                    );
+                    if member
+                        .weapon
+                        .as_ref()
+                        .is_some_and(|weapon| weapon.stats().ammo.is_none())
+                    {
+                        member.wear_weapon(io);
+                    }
                     if retaliation > 1 {
-                        println!(
+                        io.say(&format!(
                             "{} manages to neutralise {retaliation} of the zombies.",
                             member.name
-                        );
+                        ));
                     } else {
-                        println!("{} manages to neutralise a zombie.", member.name);
+                        io.say(&format!("{} manages to neutralise a zombie.", member.name));
                     }
                     zombie_count -= retaliation;
+                    member.gain_xp(retaliation * KILL_XP, rng, io);
                     self.members.insert(0, member);
                 }
                 DeathCheckResult::Dead => {
-                    print!("{} collapses to the ground, the zombies are", member.name);
-                    ellipsis();
-                    println!(" occupied.");
-                    pause();
-                    println!("For now.");
+                    self.bury(&member, Cause::Zombies);
+                    io.write(&format!(
+                        "{} collapses to the ground, the zombies are",
+                        member.name
+                    ));
+                    io.ellipsis();
+                    io.say(" occupied.");
+                    io.pause();
+                    io.say("For now.");
                     zombie_count -= attacking;
                 }
                 DeathCheckResult::Undead => {
-                    print!(
+                    self.bury(&member, Cause::Zombies);
+                    io.write(&format!(
                         "A horrendous crunch is heard, and {} collapses to the ground",
                         member.name
-                    );
-                    ellipsis();
-                    println!("\nA shriek fills the air and {} begins crawling towards the rest of the party...", member.name);
+                    ));
+                    io.ellipsis();
+                    io.say(&format!("\nA shriek fills the air and {} begins crawling towards the rest of the party...", member.name));
                     zombie_count += 1;
                 }
             };
 
-            pause();
+            io.pause();
 
             match zombie_count.cmp(&1) {
                 std::cmp::Ordering::Greater => {
-                    println!("There are now {} zombies left...", zombie_count)
+                    io.say(&format!("There are now {} zombies left...", zombie_count))
                 }
-                std::cmp::Ordering::Equal => println!("1 zombie remains..."),
-                std::cmp::Ordering::Less => println!("The attackers have been defeated..."),
+                std::cmp::Ordering::Equal => io.say("1 zombie remains..."),
+                std::cmp::Ordering::Less => io.say("The attackers have been defeated..."),
             }
         }
     }
 
-    fn choose_character() -> Member {
+    fn choose_character(io: &mut dyn Frontend) -> Member {
         loop {
-            println!("Please choose a character by selecting their number: ");
-            println!("(1) Velma");
-            println!("(2) Shaggy");
-            println!("(3) Fred");
-            println!("(4) Daphne");
-            print!(": ");
-            flush();
-
-            let input = read_line();
+            io.say("Please choose a character by selecting their number: ");
+            for (num, member) in [
+                Member::velma(),
+                Member::shaggy(),
+                Member::fred(),
+                Member::daphne(),
+            ]
+            .iter()
+            .enumerate()
+            {
+                let traits: Vec<String> = member.traits.iter().map(Trait::to_string).collect();
+                io.say(&format!(
+                    "({num}) {} ({})",
+                    member.name,
+                    traits.join(", "),
+                    num = num + 1
+                ));
+            }
+            let input = io.ask(Question::Character, ": ");
 
             match input.trim() {
                 "1" => return Member::velma(),
                 "2" => return Member::shaggy(),
                 "3" => return Member::fred(),
                 "4" => return Member::daphne(),
-                _ => println!("Invalid option."),
+                _ => io.say("Invalid option."),
+            }
+        }
+    }
+
+    /// Rewards everyone still alive for making it through another few days.
+    pub fn survive_days(&mut self, days: i32, rng: &mut GameRng, io: &mut dyn Frontend) {
+        let levels: i32 = self.members.iter().map(|member| member.level).sum();
+        for member in self.members.iter_mut() {
+            member.gain_xp(days * DAY_XP, rng, io);
+        }
+        // The main menu clears the screen, so give the player a chance to see
+        // who levelled up.
+        if self.members.iter().map(|member| member.level).sum::<i32>() > levels {
+            io.wait();
+        }
+        relationships::argue(self, rng, io);
+        morale::desert(self, rng, io);
+    }
+
+    /// Changes the morale of every member.
+    pub fn change_morale(&mut self, amount: i32) {
+        for member in self.members.iter_mut() {
+            member.change_morale(amount);
+        }
+    }
+
+    /// Whether anyone in the party has the trait.
+    pub fn has_trait(&self, member_trait: Trait) -> bool {
+        self.members.iter().any(|member| member.has(member_trait))
+    }
+
+    /// How much infection a dose of medicine cures.
+    pub fn dose(&self, rng: &mut GameRng) -> i32 {
+        let bonus = if self.has_trait(Trait::Medic) {
+            MEDIC_CURE_BONUS
+        } else {
+            0
+        };
+        rng.gen_range(5..=10) + bonus
+    }
+
+    /// How far around the mystery machine the party can see.
+    pub fn sight(&self) -> i32 {
+        if self.has_trait(Trait::Scout) {
+            SCOUT_SIGHT
+        } else {
+            1
+        }
+    }
+
+    /// Where the ammo for a type of gun is kept.
+    pub fn ammo_of(&mut self, ammo: AmmoType) -> &mut i32 {
+        match ammo {
+            AmmoType::Shells => &mut self.ammo,
+            AmmoType::Bullets => &mut self.bullets,
+        }
+    }
+
+    /// Asks which member should carry a weapon the party has found. Whatever
+    /// they were carrying is left behind.
+    pub fn arm(&mut self, weapon: Weapon, io: &mut dyn Frontend) {
+        loop {
+            io.say(&format!(
+                "Who should take the {}? Enter a number, or `leave` to leave it behind.",
+                weapon.kind
+            ));
+            for (num, member) in self.members.iter().enumerate() {
+                let carrying = match &member.weapon {
+                    Some(weapon) => weapon.to_string(),
+                    None => "nothing".to_owned(),
+                };
+                io.say(&format!(
+                    "({num}) {}, carrying {carrying}",
+                    member.name,
+                    num = num + 1
+                ));
+            }
+            let input = io.ask(Question::Equip(View::party(self), &weapon), ": ");
+            let input = input.trim().to_lowercase();
+
+            if input == "leave" {
+                io.say(&format!("You leave the {} where it lies.", weapon.kind));
+                return;
+            }
+
+            let choice = input.parse::<usize>().unwrap_or(0);
+            if choice > 0 && choice <= self.members.len() {
+                let member = &mut self.members[choice - 1];
+                io.say(&format!("{} takes the {}.", member.name, weapon.kind));
+                if let Some(old) = member.weapon.replace(weapon) {
+                    io.say(&format!("{} drops the {}.", member.name, old.kind));
+                }
+                return;
+            }
+            io.say("Invalid input.");
+        }
+    }
+
+    /// Whether the member has a gun, and the party has ammo for it.
+    pub fn loaded(&self, member: &Member) -> bool {
+        let ammo = member
+            .weapon
+            .as_ref()
+            .and_then(|weapon| weapon.stats().ammo);
+        match ammo {
+            Some(AmmoType::Shells) => self.ammo > 0,
+            Some(AmmoType::Bullets) => self.bullets > 0,
+            None => false,
+        }
+    }
+
+    /// The member at `index` fires some of the ammo for their gun as the
+    /// zombies close in. Returns how many zombies are left.
+    fn volley(
+        &mut self,
+        index: usize,
+        mut zombie_count: i32,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> i32 {
+        let kind = self.members[index]
+            .weapon
+            .as_ref()
+            .expect("member to have a gun")
+            .kind;
+        let stats = kind.stats();
+        let ammo = stats.ammo.expect("weapon to be a gun");
+        let damage = self.members[index]
+            .gun_damage()
+            .expect("member to have a gun");
+        let shots_per_kill = (ZOMBIE_HP + damage - 1) / damage;
+
+        let shots = min(
+            rng.gen_range(0..=*self.ammo_of(ammo)),
+            zombie_count * shots_per_kill,
+        );
+        let kills = shots / shots_per_kill;
+        *self.ammo_of(ammo) -= shots;
+
+        let name = self.members[index].name.clone();
+        if shots == 0 {
+            io.say(&format!("{name} had the {kind} at the ready, but was not able to draw it quick enough to attack the zombies..."));
+        } else if kills == zombie_count {
+            if zombie_count > 1 {
+                io.say(&format!("{name} quickly drew the {kind} and managed to kill all the zombies, you were lucky this time."));
+            } else {
+                io.say(&format!("{name} quickly drew the {kind} and managed to kill the attacking zombie, you were lucky this time."));
+            }
+        } else if kills > 1 {
+            io.say(&format!("{name} quickly drew the {kind} and managed to kill {kills} zombies before they could attack."));
+        } else if kills == 1 {
+            io.say(&format!("{name} quickly drew the {kind} and managed to kill one zombie before it could attack."));
+        } else {
+            io.say(&format!(
+                "{name} fired the {kind}, but the zombies kept coming."
+            ));
+        }
+        zombie_count -= kills;
+
+        let drawn = (0..shots).filter(|_| rng.gen_bool(stats.noise)).count() as i32;
+        if drawn > 0 {
+            io.pause();
+            if drawn > 1 {
+                io.say(&format!("The gunfire draws {drawn} more zombies in."));
+            } else {
+                io.say("The gunfire draws another zombie in.");
             }
+            zombie_count += drawn;
+        }
+        for _ in 0..shots {
+            self.members[index].wear_weapon(io);
         }
+        self.members[index].gain_xp(kills * KILL_XP, rng, io);
+
+        zombie_count
     }
 
-    pub fn check_infection(&mut self) {
+    pub fn check_infection(
+        &mut self,
+        surroundings: &mut Surroundings,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) {
         let mut zombies = 0;
         for _ in 0..self.members.len() {
             let mut member = self.members.pop().expect("members to be non-empty");
-            match member.check_infection() {
+            match member.check_infection(io) {
                 DeathCheckResult::Alive => self.members.insert(0, member),
                 DeathCheckResult::Dead => {
-                    print!("{} collapses on the ground, unmoving", member.name);
-                    ellipsis();
-                    println!();
+                    self.bury(&member, Cause::Infection);
+                    io.write(&format!(
+                        "{} collapses on the ground, unmoving",
+                        member.name
+                    ));
+                    io.ellipsis();
+                    io.say("");
                 }
                 DeathCheckResult::Undead => {
+                    self.bury(&member, Cause::Infection);
                     zombies += 1;
-                    print!(
+                    io.write(&format!(
                         "{} falls on the ground, and continues coughing",
                         member.name
-                    );
-                    ellipsis();
-                    println!();
+                    ));
+                    io.ellipsis();
+                    io.say("");
                 }
             }
         }
         if zombies > 0 {
             if zombies > 1 {
-                println!("Suddenly, the {zombies} corpses leap from the ground and attack the rest of the party!");
+                io.say(&format!("Suddenly, the {zombies} corpses leap from the ground and attack the rest of the party!"));
             } else {
-                println!(
-                    "Suddenly, the corpse leaps from the ground and attacks the rest of the party!"
+                io.say(
+                    "Suddenly, the corpse leaps from the ground and attacks the rest of the party!",
                 );
             }
-            pause();
-            self.combat(zombies);
+            io.pause();
+            self.combat(zombies, surroundings, rng, io);
         }
     }
 
+    pub fn bury(&mut self, member: &Member, cause: Cause) {
+        self.casualties.push(Casualty {
+            name: member.name.clone(),
+            cause,
+            // Those who starve waste away quietly, whatever they were carrying.
+            risen: cause != Cause::Starvation
+                && matches!(member.check_dead(), DeathCheckResult::Undead),
+        });
+    }
+
     pub fn check_failure(&self) -> bool {
         self.members.is_empty()
     }
 
-    pub fn display_options(&mut self, time: &mut Time, map: &mut Map, name_pool: &mut NamePool) {
-        clear();
-        println!("{time}");
-        println!("Party:\n{self}\n- Members: {}", self.members.len());
+    pub fn display_options(
+        &mut self,
+        time: &mut Time,
+        map: &mut Map,
+        name_pool: &mut NamePool,
+        content: &Content,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> Outcome {
+        io.clear();
+        io.say(&time.to_string());
+        io.say(&format!(
+            "Party:\n{self}\n- Members: {}",
+            self.members.len()
+        ));
 
-        loop {
-            println!("\nWhat is your next action?");
-            println!("(1) Show party members");
-            println!("(2) Show map");
-            println!("(3) Explore area");
-            println!("(4) Feed party");
-            println!("(5) Cure party");
-            print!(": ");
-            flush();
-
-            let input = read_line();
+        let encounter = loop {
+            io.say("\nWhat is your next action?");
+            for (num, action) in Action::iter().enumerate() {
+                io.say(&format!("({num}) {}", action.label(), num = num + 1));
+            }
+            let input = io.ask(Question::Action(View::new(self, map, time)), ": ");
 
-            println!();
+            io.say("");
 
-            match input.trim() {
-                "1" => {
-                    self.display_party_members();
+            match Action::from_input(&input) {
+                Some(Action::ShowMembers) => self.display_party_members(io),
+                Some(Action::ShowMap) => {
+                    break self.show_map(time, map, name_pool, content, rng, io)
                 }
-                "2" => self.show_map(time, map),
-                "3" => self.search(time, map, name_pool),
-                "4" => self.feed_party_members(),
-                "5" => self.cure_party_members(),
-                _ => {
-                    println!("Invalid option.");
+                Some(Action::Explore) => {
+                    return Outcome::Explored(self.search(time, map, name_pool, content, rng, io))
+                }
+                Some(Action::Feed) => self.feed_party_members(rng, io),
+                Some(Action::Cure) => self.cure_party_members(rng, io),
+                Some(Action::Rations) => upkeep::choose_rations(self, io),
+                Some(Action::Camp) => break camp::make_camp(self, time, map, rng, io),
+                Some(Action::Van) => vehicle::work_on_van(self, map, time, io),
+                Some(Action::Autopilot) => match Party::choose_autopilot_turns(io) {
+                    Some(turns) => return Outcome::Autopilot(turns),
+                    None => continue,
+                },
+                Some(Action::Settings) => settings::show_menu(io),
+                None => {
+                    io.say("Invalid option.");
                     continue;
                 }
             }
 
-            break;
+            break None;
+        };
+
+        Outcome::Other(encounter)
+    }
+
+    fn choose_autopilot_turns(io: &mut dyn Frontend) -> Option<usize> {
+        loop {
+            io.say(
+                "How many turns should the autopilot play? Enter a number, or `back` to cancel.",
+            );
+            let input = io.prompt(": ");
+
+            match input.trim().to_lowercase().as_str() {
+                "back" => return None,
+                input => match input.parse::<usize>() {
+                    Ok(turns) if turns > 0 => return Some(turns),
+                    _ => io.say("Invalid input."),
+                },
+            }
         }
     }
 
-    fn show_map(&mut self, time: &mut Time, map: &mut Map) {
+    /// Shows the map and lets the player travel until they close it, or until
+    /// the party runs into something on the way, which is returned.
+    fn show_map(
+        &mut self,
+        time: &mut Time,
+        map: &mut Map,
+        name_pool: &mut NamePool,
+        content: &Content,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> Option<Encounter> {
         loop {
-            clear();
-            println!("{time}");
-            println!("{}", map);
-            println!("Travel will cost {TRAVEL_COST} fuel and will take {TRAVEL_TIME} hours.");
-            if self.fuel >= TRAVEL_COST {
-                println!("You have {} fuel.", self.fuel);
-                loop {
-                    println!("Enter a compass direction to travel, or `back` to close the map.");
-                    print!(": ");
-                    flush();
-
-                    let mut input = String::new();
-                    let stdin = std::io::stdin();
-                    stdin.read_line(&mut input).expect("valid input");
-
-                    match input.to_lowercase().trim() {
-                        "north" => map.travel(Direction::North),
-                        "east" => map.travel(Direction::East),
-                        "south" => map.travel(Direction::South),
-                        "west" => map.travel(Direction::West),
-                        "back" => return,
-                        _ => {
-                            println!("Invalid option.");
-                            continue;
-                        }
+            io.clear();
+            io.say(&time.to_string());
+            io.say(&map.to_string());
+            let on_foot = map.on_foot();
+            for direction in Direction::iter() {
+                let tile = map.neighbour(direction);
+                let road = if tile.road { " by road" } else { "" };
+                if on_foot {
+                    io.say(&format!(
+                        "- {}: {}, {} hours on foot",
+                        direction.input(),
+                        tile.terrain,
+                        tile.walk_time()
+                    ));
+                } else if tile.drivable() {
+                    io.say(&format!(
+                        "- {}: {}{road}, {} hours, {} fuel",
+                        direction.input(),
+                        tile.terrain,
+                        tile.travel_time(),
+                        self.van.fuel_cost(tile.fuel_cost())
+                    ));
+                } else {
+                    io.say(&format!(
+                        "- {}: {}, on foot only",
+                        direction.input(),
+                        tile.terrain
+                    ));
+                }
+            }
+            if on_foot {
+                io.say("The party is on foot. The mystery machine is parked where the M is on the map.");
+            } else if self.van.broken_down() {
+                io.say("The mystery machine has broken down. It needs repairing with scrap before it can go anywhere.");
+            }
+            io.say(&format!("You have {} fuel.", self.fuel));
+
+            loop {
+                io.say("Enter a compass direction to travel, or `back` to close the map.");
+                let input = io.ask(Question::Direction(View::new(self, map, time)), ": ");
+                let input = input.to_lowercase();
+
+                let direction = match Direction::from_input(&input) {
+                    Some(direction) => direction,
+                    None if input.trim() == "back" => return None,
+                    None => {
+                        io.say("Invalid option.");
+                        continue;
                     }
+                };
 
-                    time.advance(TRAVEL_TIME);
-                    self.fuel -= TRAVEL_COST;
-                    print!("The party packs into the mystery machine, and you spend the next {TRAVEL_TIME} hours travelling");
-                    ellipsis();
-                    ellipsis();
-                    println!();
-                    break;
+                let tile = map.neighbour(direction);
+                let fuel = self.van.fuel_cost(tile.fuel_cost());
+                if !on_foot && (self.van.broken_down() || !tile.drivable() || fuel > self.fuel) {
+                    if self.van.broken_down() {
+                        io.say("The mystery machine will not start.");
+                    } else if tile.drivable() {
+                        io.say("You do not have enough fuel to drive that way.");
+                    } else {
+                        io.say(&format!(
+                            "The mystery machine cannot cross the {} there.",
+                            tile.terrain.to_string().to_lowercase()
+                        ));
+                    }
+                    if !leave_van(View::new(self, map, time), io) {
+                        continue;
+                    }
+                    map.leave_van();
+                    io.say("The party grabs what they can carry, and leaves the mystery machine behind.");
                 }
-            } else {
-                println!("You do not have enough fuel to travel.");
-                wait();
-                return;
+
+                let ambush = if map.on_foot() {
+                    travel::walk(self, direction, time, map, rng, io)
+                } else {
+                    travel::drive(self, direction, time, map, rng, io);
+                    None
+                };
+                let encounter = ambush
+                    .or_else(|| travel::encounter(self, name_pool, content, time, map, rng, io));
+                if encounter.is_some() {
+                    // Stop to deal with whatever the party ran into.
+                    return encounter;
+                }
+                break;
             }
         }
     }
 
-    fn cure_party_members(&mut self) {
+    fn cure_party_members(&mut self, rng: &mut GameRng, io: &mut dyn Frontend) {
         loop {
-            clear();
+            io.clear();
             for (num, member) in self.members.iter().enumerate() {
-                println!("({num}) {member}\n", num = num + 1);
+                io.say(&format!("({num}) {member}\n", num = num + 1));
             }
-            println!("You have {} medicine.", self.medicine);
+            io.say(&format!("You have {} medicine.", self.medicine));
 
             loop {
-                println!("Enter a number to attempt to cure a party member, or 'back' to exit this menu.");
+                io.say("Enter a number to attempt to cure a party member, or 'back' to exit this menu.");
 
-                print!(": ");
-                flush();
-                let mut input = String::new();
-                let stdin = std::io::stdin();
-                stdin.read_line(&mut input).expect("valid input");
+                let input = io.ask(Question::Cure(View::party(self)), ": ");
                 let input = input.trim().to_lowercase();
 
                 if input == "back" {
@@ -339,48 +870,41 @@ impl Party {
                 let choice = input.parse::<usize>().unwrap_or(0);
 
                 if choice > 0 && choice <= self.members.len() {
-                    let member = self
-                        .members
-                        .get_mut(choice - 1)
-                        .expect("choice to be in bounds");
-
                     if self.medicine > 0 {
-                        print!("{} takes some of the antibiotics", member.name);
-                        ellipsis();
-                        println!("\nThey feel slightly better now.");
-                        member.cure(thread_rng().gen_range(5..=10));
+                        let dose = self.dose(rng);
+                        let member = &mut self.members[choice - 1];
+                        io.write(&format!("{} takes some of the antibiotics", member.name));
+                        io.ellipsis();
+                        io.say("\nThey feel slightly better now.");
+                        member.cure(dose);
                         self.medicine -= 1;
-                        wait();
+                        io.wait();
                         break;
                     } else {
-                        println!(
+                        io.say(&format!(
                             "You do not have enough medicine for {} to use...",
-                            member.name
-                        );
+                            self.members[choice - 1].name
+                        ));
                     }
                 } else {
-                    println!("Invalid input.");
+                    io.say("Invalid input.");
                 }
             }
         }
     }
 
-    fn feed_party_members(&mut self) {
+    fn feed_party_members(&mut self, rng: &mut GameRng, io: &mut dyn Frontend) {
         loop {
-            clear();
+            io.clear();
             for (num, member) in self.members.iter().enumerate() {
-                println!("({num}) {member}\n", num = num + 1);
+                io.say(&format!("({num}) {member}\n", num = num + 1));
             }
-            println!("You have {} food.", self.food);
+            io.say(&format!("You have {} food.", self.food));
 
             loop {
-                println!("Enter a number to feed a party member, or 'back' to exit this menu.");
+                io.say("Enter a number to feed a party member, or 'back' to exit this menu.");
 
-                print!(": ");
-                flush();
-                let mut input = String::new();
-                let stdin = std::io::stdin();
-                stdin.read_line(&mut input).expect("valid input");
+                let input = io.ask(Question::Feed(View::party(self)), ": ");
                 let input = input.trim().to_lowercase();
 
                 if input == "back" {
@@ -390,62 +914,231 @@ impl Party {
                 let choice = input.parse::<usize>().unwrap_or(0);
 
                 if choice > 0 && choice <= self.members.len() {
+                    // Anyone hungrier than the member being fed takes note.
+                    let fed = &self.members[choice - 1];
+                    if self.food >= fed.meal() {
+                        for other in self.members.iter() {
+                            if other.satiation < fed.satiation {
+                                self.relationships
+                                    .change(&fed.name, &other.name, -FEED_RESENTMENT);
+                            }
+                        }
+                    }
+
                     let member = self
                         .members
                         .get_mut(choice - 1)
                         .expect("choice to be in bounds");
 
-                    if self.food > 0 {
-                        print!("{} begins eating", member.name);
-                        ellipsis();
-                        println!("\nThey feel slightly better now.");
-                        member.heal(thread_rng().gen_range(2..=4));
-                        self.food -= 1;
-                        wait();
+                    if self.food >= member.meal() {
+                        io.write(&format!("{} begins eating", member.name));
+                        io.ellipsis();
+                        io.say("\nThey feel slightly better now.");
+                        member.heal(rng.gen_range(2..=4));
+                        member.satiation = SATIATED;
+                        member.change_morale(MEAL_MORALE);
+                        self.food -= member.meal();
+                        io.wait();
                         break;
                     } else {
-                        println!("You do not have enough food for {} to eat...", member.name);
+                        io.say(&format!(
+                            "You do not have enough food for {} to eat...",
+                            member.name
+                        ));
                     }
                 } else {
-                    println!("Invalid input.");
+                    io.say("Invalid input.");
                 }
             }
         }
     }
 
-    fn display_party_members(&self) {
-        clear();
+    fn display_party_members(&self, io: &mut dyn Frontend) {
+        io.clear();
         for member in self.members.iter() {
-            println!("{}\n", member);
+            io.say(&format!(
+                "{member}\n{}\n",
+                relationships::describe(self, member)
+            ));
         }
-        wait();
+        io.wait();
     }
 
-    fn search(&mut self, time: &mut Time, map: &mut Map, name_pool: &mut NamePool) {
+    fn search(
+        &mut self,
+        time: &mut Time,
+        map: &mut Map,
+        name_pool: &mut NamePool,
+        content: &Content,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> Encounter {
         let tile = map.get_tile(None);
 
-        clear();
-        println!("{}", time);
-        println!("Party:\n{}\n- Members: {}\n", self, self.members.len());
+        io.clear();
+        io.say(&time.to_string());
+        io.say(&format!(
+            "Party:\n{}\n- Members: {}\n",
+            self,
+            self.members.len()
+        ));
+
+        // Locations from mods that have since been removed play out like a
+        // normal tile.
+        let scene = match &tile.location_type {
+            Some(location) if !tile.explored => content.locations.get(location),
+            _ => None,
+        };
 
-        match &tile.location_type {
+        let horde = tile.horde;
+        let landmark = tile.landmark.clone().filter(|_| !tile.explored);
+        let missing = campaign::missing(map);
+        let on_foot = map.on_foot();
+
+        let mut surroundings = Surroundings {
+            time,
+            on_foot,
+            tile: map.get_tile_mut(None),
+        };
+        let mut fled = false;
+        let encounter = match scene {
+            // Roaming Horde
+            _ if horde > 0 => {
+                surroundings.tile.horde = 0;
+                if horde > 1 {
+                    io.say(&format!("The {horde} zombies the party fled from are still roaming the area, and they have picked up your scent!"));
+                } else {
+                    io.say("The zombie the party fled from is still roaming the area, and it has picked up your scent!");
+                }
+                io.pause();
+                self.combat(horde, &mut surroundings, rng, io);
+                Encounter::Event(Event::Zombie(horde))
+            }
+            // Campaign
+            _ if landmark.is_some() => match landmark.expect("landmark to be present") {
+                Landmark::Hideout(name) => {
+                    campaign::reunite(&name, self, io);
+                    Encounter::Landmark(Landmark::Hideout(name))
+                }
+                Landmark::Evacuation if campaign::evacuate(&missing, io) => Encounter::Escaped,
+                Landmark::Evacuation => Encounter::Landmark(Landmark::Evacuation),
+            },
             // Special Location
-            Some(location) if !tile.explored => location.handle(self, name_pool),
+            Some(scene) => {
+                fled = scene.play(self, name_pool, &mut surroundings, rng, io);
+                Encounter::Location(scene.name.clone())
+            }
             // Normal Tile
-            _ => {
-                let options = tile.get_event_options(time);
-                Event::roll(options).handle(self, name_pool);
+            None => {
+                let options = content.events.options(
+                    surroundings.tile.location_type.as_ref(),
+                    surroundings.tile.terrain,
+                    surroundings.time,
+                );
+                let event = Event::roll(options, rng);
+                let fight = event.handle(self, name_pool, &mut surroundings, rng, io);
+                fled = fight == Some(combat::Outcome::Fled);
+                Encounter::Event(event)
             }
-        }
+        };
 
-        self.normalise_values();
-        time.advance(1);
+        self.normalise_values(!surroundings.on_foot, io);
+        surroundings.time.advance(1);
 
         if !self.check_failure() {
-            self.check_infection();
-            wait();
+            for member in self.members.iter_mut() {
+                member.gain_xp(SEARCH_XP, rng, io);
+            }
+            self.check_infection(&mut surroundings, rng, io);
+            io.wait();
         }
 
-        map.explore();
+        // Clues to the gang turn up among the ordinary tiles.
+        if let Encounter::Event(_) = encounter {
+            if !missing.is_empty() && !self.check_failure() && rng.gen_bool(CLUE_CHANCE) {
+                campaign::clue(map, rng, io);
+            }
+        }
+
+        // A location is only explored once the horde in the way is dealt with,
+        // rather than run from, and the evacuation point stays put until the
+        // party leaves from it.
+        if horde == 0 && !fled && !matches!(encounter, Encounter::Landmark(Landmark::Evacuation)) {
+            map.explore();
+        }
+
+        encounter
+    }
+}
+
+/// Asks whether to leave the mystery machine behind and go on foot.
+fn leave_van(view: View, io: &mut dyn Frontend) -> bool {
+    loop {
+        io.say("Leave the mystery machine here and go on foot? (y/n)");
+        match io.ask(Question::LeaveVan(view), ": ").to_lowercase().trim() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => io.say("Invalid input."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, rng};
+
+    #[test]
+    fn leaving_the_van_takes_yes_or_no_in_any_case() {
+        let party = Party::create(&mut rng::seeded(1), &mut Scripted::new(["1"]));
+        let view = View::party(&party);
+
+        let mut io = Scripted::new(["maybe", " Yes "]);
+        assert!(leave_van(view, &mut io));
+        assert!(io.finished());
+        assert!(leave_van(view, &mut Scripted::new(["y"])));
+        assert!(!leave_van(view, &mut Scripted::new(["NO"])));
+        assert!(!leave_van(view, &mut Scripted::new(["n"])));
+    }
+
+    #[test]
+    fn supplies_left_behind_are_reported() {
+        let mut party = Party::create(&mut rng::seeded(1), &mut Scripted::new(["1"]));
+        party.fuel = party.van.tank() + 2;
+        party.medicine = 3;
+        party.food = party.van.storage();
+
+        let mut io = Scripted::default();
+        party.normalise_values(true, &mut io);
+        assert_eq!(party.fuel, party.van.tank());
+        assert_eq!(party.medicine, 3);
+        assert_eq!(party.food, party.van.storage() - 3);
+        assert!(io.output().contains("-2 fuel (the tank is full)"));
+        assert!(io
+            .output()
+            .contains("-3 food (no room in the mystery machine)"));
+        assert!(!io.output().contains("medicine"));
+
+        let mut io = Scripted::default();
+        party.food = 0;
+        party.medicine = party.van.storage() + 4;
+        party.normalise_values(true, &mut io);
+        assert_eq!(
+            io.output(),
+            "-4 medicine (no room in the mystery machine)\n"
+        );
+    }
+
+    #[test]
+    fn supplies_are_only_capped_with_the_van() {
+        let mut party = Party::create(&mut rng::seeded(1), &mut Scripted::new(["1"]));
+        party.fuel = party.van.tank() + 2;
+        party.food = party.van.storage() + 2;
+
+        let mut io = Scripted::default();
+        party.normalise_values(false, &mut io);
+        assert_eq!(party.fuel, party.van.tank() + 2);
+        assert_eq!(party.food, party.van.storage() + 2);
+        assert_eq!(io.output(), "");
     }
 }
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+
+use rand::{seq::IteratorRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    io::Frontend,
+    member::Member,
+    party::{Casualty, Cause, Party},
+    rng::GameRng,
+};
+
+/// How far affinity can go either way.
+const MAX_AFFINITY: i32 = 10;
+/// Where the gang starts with each other, having been through it all before.
+const GANG_AFFINITY: i32 = 5;
+/// Members at or above this affinity are close, and grieve harder for each
+/// other.
+const CLOSE_AFFINITY: i32 = 5;
+/// Members at or above this affinity would die for each other.
+const DEVOTED_AFFINITY: i32 = 8;
+/// Members at or below this affinity are at odds, and argue.
+const RIVAL_AFFINITY: i32 = -5;
+
+/// How much closer surviving a fight together brings members.
+const FIGHT_AFFINITY: i32 = 1;
+/// How much closer a member gets to someone they stood in front of.
+pub const RESCUE_AFFINITY: i32 = 2;
+/// How much a hungry member resents someone eating ahead of them.
+pub const FEED_RESENTMENT: i32 = 1;
+/// How much worse a death is for those who were close.
+const BEREAVED_MORALE: i32 = 2;
+/// How much an argument costs both sides.
+const ARGUMENT_MORALE: i32 = 1;
+
+/// The chance of rivals falling out every day.
+const ARGUMENT_CHANCE: f64 = 0.3;
+/// The chance of a devoted member taking a killing blow meant for someone
+/// else.
+const SACRIFICE_CHANCE: f64 = 0.5;
+/// The chance of a close friend refusing to run while their friend's body is
+/// still lying there.
+const GRIEVING_CHANCE: f64 = 0.5;
+
+/// How every pair of members feels about each other, by name. Each bond is
+/// kept under both names.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Relationships {
+    bonds: BTreeMap<String, BTreeMap<String, i32>>,
+}
+
+impl Relationships {
+    /// How two members feel about each other. Strangers start at zero.
+    pub fn affinity(&self, a: &str, b: &str) -> i32 {
+        match self.bonds.get(a).and_then(|bonds| bonds.get(b)) {
+            Some(&affinity) => affinity,
+            None if Member::gang(a).is_some() && Member::gang(b).is_some() => GANG_AFFINITY,
+            None => 0,
+        }
+    }
+
+    pub fn change(&mut self, a: &str, b: &str, amount: i32) {
+        if a == b {
+            return;
+        }
+        let affinity = (self.affinity(a, b) + amount).clamp(-MAX_AFFINITY, MAX_AFFINITY);
+        for (from, to) in [(a, b), (b, a)] {
+            self.bonds
+                .entry(from.to_owned())
+                .or_default()
+                .insert(to.to_owned(), affinity);
+        }
+    }
+
+    /// Changes how every pair of the named members feel about each other.
+    fn change_all(&mut self, names: &[&str], amount: i32) {
+        for &a in names {
+            let mut bonds = self.bonds.remove(a).unwrap_or_default();
+            for &b in names.iter().filter(|&&b| b != a) {
+                match bonds.get_mut(b) {
+                    Some(affinity) => {
+                        *affinity = (*affinity + amount).clamp(-MAX_AFFINITY, MAX_AFFINITY)
+                    }
+                    None => {
+                        let affinity =
+                            (self.affinity(a, b) + amount).clamp(-MAX_AFFINITY, MAX_AFFINITY);
+                        bonds.insert(b.to_owned(), affinity);
+                    }
+                }
+            }
+            self.bonds.insert(a.to_owned(), bonds);
+        }
+    }
+
+    /// Drops every bond with someone who has left the party for good.
+    pub fn forget(&mut self, name: &str) {
+        if let Some(bonds) = self.bonds.remove(name) {
+            for other in bonds.keys() {
+                if let Some(theirs) = self.bonds.get_mut(other) {
+                    theirs.remove(name);
+                }
+            }
+        }
+    }
+
+    pub fn close(&self, a: &str, b: &str) -> bool {
+        self.affinity(a, b) >= CLOSE_AFFINITY
+    }
+
+    pub fn rivals(&self, a: &str, b: &str) -> bool {
+        self.affinity(a, b) <= RIVAL_AFFINITY
+    }
+
+    fn devoted(&self, a: &str, b: &str) -> bool {
+        self.affinity(a, b) >= DEVOTED_AFFINITY
+    }
+}
+
+/// Lists who the member is close to and at odds with, for the members menu.
+pub fn describe(party: &Party, member: &Member) -> String {
+    let names = |filter: &dyn Fn(&str) -> bool| {
+        let names: Vec<&str> = party
+            .members
+            .iter()
+            .map(|other| other.name.as_str())
+            .filter(|other| *other != member.name && filter(other))
+            .collect();
+        if names.is_empty() {
+            "Nobody".to_owned()
+        } else {
+            names.join(", ")
+        }
+    };
+    format!(
+        "- Close to: {}\n- At odds with: {}",
+        names(&|other| party.relationships.close(&member.name, other)),
+        names(&|other| party.relationships.rivals(&member.name, other)),
+    )
+}
+
+/// Brings everyone who made it through a fight a little closer.
+pub fn shared_fight(party: &mut Party) {
+    let names: Vec<&str> = party
+        .members
+        .iter()
+        .map(|member| member.name.as_str())
+        .collect();
+    party.relationships.change_all(&names, FIGHT_AFFINITY);
+}
+
+/// Those who were close to the dead take it harder than the rest.
+pub fn bereave(party: &mut Party, casualties: &[Casualty], io: &mut dyn Frontend) {
+    for casualty in casualties {
+        for member in party.members.iter_mut() {
+            if casualty.cause != Cause::Deserted
+                && party.relationships.close(&member.name, &casualty.name)
+            {
+                io.say(&format!(
+                    "{} cannot stop thinking about {}.",
+                    member.name, casualty.name
+                ));
+                member.change_morale(-BEREAVED_MORALE);
+            }
+        }
+    }
+}
+
+/// Rivals may fall out, costing both some morale and driving them further
+/// apart.
+pub fn argue(party: &mut Party, rng: &mut GameRng, io: &mut dyn Frontend) {
+    let rivals = party
+        .members
+        .iter()
+        .enumerate()
+        .flat_map(|(index, member)| {
+            party.members[index + 1..]
+                .iter()
+                .filter(|other| party.relationships.rivals(&member.name, &other.name))
+                .map(|other| (member.name.clone(), other.name.clone()))
+        })
+        .choose(rng);
+
+    let Some((a, b)) = rivals else {
+        return;
+    };
+    if !rng.gen_bool(ARGUMENT_CHANCE) {
+        return;
+    }
+
+    io.say(&format!(
+        "{a} and {b} get into a shouting match over nothing at all, and the whole party is on edge afterwards."
+    ));
+    party.relationships.change(&a, &b, -1);
+    for member in party.members.iter_mut() {
+        if member.name == a || member.name == b {
+            member.change_morale(-ARGUMENT_MORALE);
+        }
+    }
+    io.wait();
+}
+
+/// The index of someone devoted to the named member who would throw themself
+/// in front of a killing blow meant for them, if anyone does.
+pub fn sacrifice(party: &Party, target: &str, rng: &mut GameRng) -> Option<usize> {
+    let devoted = party
+        .members
+        .iter()
+        .enumerate()
+        .filter(|(_, member)| {
+            member.name != target && party.relationships.devoted(target, &member.name)
+        })
+        .map(|(index, _)| index)
+        .choose(rng)?;
+    rng.gen_bool(SACRIFICE_CHANCE).then_some(devoted)
+}
+
+/// Someone who will not run while a close friend who fell earlier lies
+/// there, if anyone.
+pub fn grieving<'a>(party: &'a Party, rng: &mut GameRng) -> Option<(&'a str, &'a str)> {
+    let (member, casualty) = party
+        .members
+        .iter()
+        .flat_map(|member| {
+            party
+                .casualties
+                .iter()
+                .filter(|casualty| casualty.cause == Cause::Zombies)
+                .filter(|casualty| party.relationships.close(&member.name, &casualty.name))
+                .map(move |casualty| (member, casualty))
+        })
+        .choose(rng)?;
+    rng.gen_bool(GRIEVING_CHANCE)
+        .then_some((member.name.as_str(), casualty.name.as_str()))
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::VecDeque,
+    fs::{read_to_string, write},
+    path::Path,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    content::Content,
+    io::{Frontend, Question},
+    settings::TextSpeed,
+};
+
+/// The replay format written by this build of the game. Bump it whenever a
+/// change to the game means recorded input would play back differently.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Everything needed to play a run back exactly: the seed it started from, the
+/// content it was played with and every line the player typed since.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    /// Replays from before the format was versioned count as version 0.
+    #[serde(default)]
+    pub version: u32,
+    /// The [`Content::fingerprint`] of the event tables and scenes in use.
+    #[serde(default)]
+    pub content: String,
+    pub seed: u64,
+    pub inputs: Vec<String>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, content: &Content) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            content: content.fingerprint(),
+            seed,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+
+    pub fn save(&self, path: &Path) {
+        let json = serde_json::to_string(self).expect("serialize to succeed");
+        write(path, json).expect("write to succeed");
+    }
+
+    /// Fails if the replay would not play back the way it was recorded,
+    /// because it was made by a different build of the game or with different
+    /// event tables or scenes.
+    pub fn check(&self, content: &Content) -> Result<(), String> {
+        if self.version != REPLAY_VERSION {
+            Err(format!(
+                "it was recorded in replay format {}, and this build plays format {REPLAY_VERSION}",
+                self.version
+            ))
+        } else if self.content != content.fingerprint() {
+            Err("it was recorded with different event tables or mods".to_owned())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Passes everything through to another frontend, while appending each line
+/// the player types to a [`Replay`].
+pub struct Recording<'a> {
+    inner: &'a mut dyn Frontend,
+    replay: &'a mut Replay,
+}
+
+impl<'a> Recording<'a> {
+    pub fn new(inner: &'a mut dyn Frontend, replay: &'a mut Replay) -> Self {
+        Self { inner, replay }
+    }
+
+    fn record(&mut self, line: &str) {
+        self.replay
+            .inputs
+            .push(line.trim_end_matches(['\r', '\n']).to_owned());
+    }
+}
+
+impl Frontend for Recording<'_> {
+    fn write(&mut self, text: &str) {
+        self.inner.write(text);
+    }
+
+    fn read_line(&mut self) -> String {
+        let line = self.inner.read_line();
+        self.record(&line);
+        line
+    }
+
+    fn ask(&mut self, question: Question, text: &str) -> String {
+        let line = self.inner.ask(question, text);
+        self.record(&line);
+        line
+    }
+
+    fn wait(&mut self) {
+        self.inner.wait();
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.inner.sleep(duration);
+    }
+
+    fn text_speed(&self) -> TextSpeed {
+        self.inner.text_speed()
+    }
+
+    fn set_text_speed(&mut self, speed: TextSpeed) {
+        self.inner.set_text_speed(speed);
+    }
+}
+
+/// Answers every prompt from a recorded [`Replay`], echoing the input as if it
+/// had been typed. Once the recording runs out, the player takes over.
+pub struct Playback<'a> {
+    inner: &'a mut dyn Frontend,
+    inputs: VecDeque<String>,
+    full_speed: bool,
+    handed_over: bool,
+}
+
+impl<'a> Playback<'a> {
+    pub fn new(inner: &'a mut dyn Frontend, replay: Replay, full_speed: bool) -> Self {
+        Self {
+            inner,
+            inputs: replay.inputs.into(),
+            full_speed,
+            handed_over: false,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Echoes `prompt` and the next recorded line, returning the line, or
+    /// announces that the player is taking over once there are none left.
+    fn next_input(&mut self, prompt: &str) -> Option<String> {
+        match self.inputs.pop_front() {
+            Some(line) => {
+                self.inner.write(prompt);
+                self.inner.write(&line);
+                self.inner.write("\n");
+                Some(line)
+            }
+            None => {
+                if !self.handed_over {
+                    self.handed_over = true;
+                    self.inner
+                        .say("\n[The replay has ended, continuing interactively.]");
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Frontend for Playback<'_> {
+    fn write(&mut self, text: &str) {
+        self.inner.write(text);
+    }
+
+    fn read_line(&mut self) -> String {
+        match self.next_input("") {
+            Some(line) => line,
+            None => self.inner.read_line(),
+        }
+    }
+
+    fn ask(&mut self, question: Question, text: &str) -> String {
+        match self.next_input(text) {
+            Some(line) => line,
+            None => self.inner.ask(question, text),
+        }
+    }
+
+    fn wait(&mut self) {
+        if self.handed_over {
+            self.inner.wait();
+        } else {
+            self.pause();
+            self.inner.write("\n");
+        }
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        if !self.full_speed {
+            self.inner.sleep(duration);
+        }
+    }
+
+    fn text_speed(&self) -> TextSpeed {
+        if self.full_speed {
+            TextSpeed::Instant
+        } else {
+            self.inner.text_speed()
+        }
+    }
+
+    // Speed changes recorded in the replay are not applied to the settings.
+    fn set_text_speed(&mut self, speed: TextSpeed) {
+        if self.handed_over {
+            self.inner.set_text_speed(speed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event_table::EventTables, io::Scripted, location::Locations, mapgen::MapParams};
+
+    fn content() -> Content {
+        Content::new(
+            EventTables::built_in(),
+            Locations::built_in(),
+            MapParams::default(),
+        )
+        .expect("content to be valid")
+    }
+
+    #[test]
+    fn replay_plays_back_with_the_same_content() {
+        let content = content();
+        assert!(Replay::new(1, &content).check(&content).is_ok());
+    }
+
+    #[test]
+    fn replay_with_other_content_is_refused() {
+        let content = content();
+        let mut replay = Replay::new(1, &content);
+        replay.content = "0123456789abcdef0123456789abcdef".to_owned();
+        assert!(replay.check(&content).is_err());
+    }
+
+    #[test]
+    fn unversioned_replay_is_refused() {
+        let replay: Replay =
+            serde_json::from_str(r#"{"game_version":"0.1.0","seed":1,"inputs":["2","1"]}"#)
+                .expect("replay to load");
+        assert!(replay.check(&content()).is_err());
+    }
+
+    #[test]
+    fn playback_hands_over_without_repeating_the_prompt() {
+        let content = content();
+        let mut replay = Replay::new(1, &content);
+        replay.inputs.push("recorded".to_owned());
+        let mut player = Scripted::new(["typed"]);
+        let mut playback = Playback::new(&mut player, replay, true);
+
+        assert_eq!(playback.prompt(": "), "recorded");
+        assert_eq!(playback.prompt(": "), "typed");
+        assert_eq!(
+            player.output(),
+            ": recorded\n: \n[The replay has ended, continuing interactively.]\ntyped\n"
+        );
+    }
+}
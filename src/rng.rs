@@ -0,0 +1,16 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The single random number generator shared by every part of the game.
+///
+/// Its full state is serialised with the rest of the savegame, so a loaded run
+/// continues with exactly the same sequence of rolls it would have had.
+pub type GameRng = ChaCha8Rng;
+
+pub fn seeded(seed: u64) -> GameRng {
+    GameRng::seed_from_u64(seed)
+}
+
+pub fn random_seed() -> u64 {
+    rand::random()
+}
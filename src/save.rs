@@ -0,0 +1,233 @@
+use std::{
+    env,
+    fmt::Display,
+    fs::{copy, create_dir_all, read_dir, read_to_string, remove_file, rename, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    game::Game,
+    migration::{self, MigrationError, CURRENT_VERSION},
+    replay::Replay,
+};
+
+const EXTENSION: &str = "json";
+const REPLAY_EXTENSION: &str = "replay";
+const LEGACY_SAVE_LOCATION: &str = "savegame.json";
+const LEGACY_SLOT_NAME: &str = "savegame";
+
+/// Why a slot could not be turned back into a [`Game`].
+pub enum LoadError {
+    Read(io::Error),
+    Parse(serde_json::Error),
+    Migration(MigrationError),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Read(error) => write!(f, "the file could not be read: {error}"),
+            LoadError::Parse(error) => write!(f, "the file is not a valid savegame: {error}"),
+            LoadError::Migration(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// The header written at the top of every savegame.
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+/// The directory holding every save slot.
+pub struct Saves {
+    dir: PathBuf,
+}
+
+/// A single named savegame inside the save directory.
+pub struct Slot {
+    pub name: String,
+    path: PathBuf,
+}
+
+impl Saves {
+    /// Opens the save directory, creating it if needed. Without an explicit
+    /// directory, the per-user data directory for the platform is used.
+    pub fn open(dir: Option<PathBuf>) -> io::Result<Self> {
+        let dir = dir.unwrap_or_else(default_dir);
+        create_dir_all(&dir)?;
+
+        let saves = Self { dir };
+        saves.import_legacy_save();
+
+        Ok(saves)
+    }
+
+    /// Lists every slot, sorted by name.
+    pub fn slots(&self) -> Vec<Slot> {
+        let mut slots: Vec<Slot> = match read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+                .filter_map(|path| {
+                    let name = path.file_stem()?.to_str()?.to_owned();
+                    Some(Slot { name, path })
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        slots.sort_by(|a, b| a.name.cmp(&b.name));
+
+        slots
+    }
+
+    /// Returns the slot with the given name, whether or not it exists yet.
+    pub fn slot(&self, name: &str) -> Result<Slot, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Savegame names cannot be empty.".to_owned());
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+        {
+            return Err(
+                "Savegame names may only contain letters, numbers, spaces, `-` and `_`.".to_owned(),
+            );
+        }
+
+        Ok(Slot {
+            name: name.to_owned(),
+            path: self.dir.join(format!("{name}.{EXTENSION}")),
+        })
+    }
+
+    /// Returns a slot name of the form "Run N" that is not yet taken.
+    pub fn unused_name(&self) -> String {
+        let slots = self.slots();
+        (1..)
+            .map(|n| format!("Run {n}"))
+            .find(|name| !slots.iter().any(|slot| &slot.name == name))
+            .expect("an unused name to exist")
+    }
+
+    pub fn rename(&self, slot: &Slot, new_name: &str) -> Result<Slot, String> {
+        let target = self.vacant_slot(new_name)?;
+        rename(&slot.path, &target.path).map_err(|error| error.to_string())?;
+        if slot.replay_path().is_file() {
+            rename(slot.replay_path(), target.replay_path()).map_err(|error| error.to_string())?;
+        }
+        Ok(target)
+    }
+
+    pub fn copy(&self, slot: &Slot, new_name: &str) -> Result<Slot, String> {
+        let target = self.vacant_slot(new_name)?;
+        copy(&slot.path, &target.path).map_err(|error| error.to_string())?;
+        if slot.replay_path().is_file() {
+            copy(slot.replay_path(), target.replay_path()).map_err(|error| error.to_string())?;
+        }
+        Ok(target)
+    }
+
+    /// Like [`Saves::slot`], but fails if the slot is already in use.
+    pub fn vacant_slot(&self, name: &str) -> Result<Slot, String> {
+        let slot = self.slot(name)?;
+        if slot.exists() {
+            return Err(format!("A savegame named `{}` already exists.", slot.name));
+        }
+        Ok(slot)
+    }
+
+    /// Moves a savegame left in the working directory by older versions of the
+    /// game into its own slot, so it shows up in the load menu.
+    fn import_legacy_save(&self) {
+        let legacy = Path::new(LEGACY_SAVE_LOCATION);
+        if !legacy.is_file() {
+            return;
+        }
+
+        let Ok(slot) = self.vacant_slot(LEGACY_SLOT_NAME) else {
+            return;
+        };
+        if copy(legacy, &slot.path).is_ok() {
+            let _ = remove_file(legacy);
+        }
+    }
+}
+
+impl Slot {
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the savegame, upgrading it from older save formats if needed.
+    pub fn load(&self) -> Result<Game, LoadError> {
+        let json = read_to_string(&self.path).map_err(LoadError::Read)?;
+        let save = serde_json::from_str(&json).map_err(LoadError::Parse)?;
+        let game = migration::upgrade(save).map_err(LoadError::Migration)?;
+        serde_json::from_value(game).map_err(LoadError::Parse)
+    }
+
+    pub fn save(&self, game: &Game) {
+        let save = SaveFile {
+            version: CURRENT_VERSION,
+            game,
+        };
+        let json = serde_json::to_string(&save).expect("serialize to succeed");
+        write(&self.path, json).expect("write to succeed");
+    }
+
+    /// Deletes the savegame, keeping its replay so the run can still be
+    /// watched back.
+    pub fn delete(&self) {
+        let _ = remove_file(&self.path);
+    }
+
+    /// Deletes both the savegame and its replay.
+    pub fn delete_all(&self) {
+        self.delete();
+        let _ = remove_file(self.replay_path());
+    }
+
+    /// The file recording every input of the run, next to the savegame.
+    pub fn replay_path(&self) -> PathBuf {
+        self.path.with_extension(REPLAY_EXTENSION)
+    }
+
+    /// Reads the slot's replay, if the whole run has been recorded.
+    pub fn load_replay(&self) -> Option<Replay> {
+        Replay::load(&self.replay_path()).ok()
+    }
+
+    pub fn save_replay(&self, replay: &Replay) {
+        replay.save(&self.replay_path());
+    }
+}
+
+/// The per-user directory the game keeps its files in.
+pub fn data_dir() -> PathBuf {
+    let data_dir = if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        PathBuf::from(dir)
+    } else if let Some(dir) = env::var_os("APPDATA") {
+        PathBuf::from(dir)
+    } else if let Some(home) = env::var_os("HOME") {
+        PathBuf::from(home).join(".local").join("share")
+    } else {
+        PathBuf::from(".")
+    };
+
+    data_dir.join("zombie-apocalypse")
+}
+
+fn default_dir() -> PathBuf {
+    data_dir().join("saves")
+}
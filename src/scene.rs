@@ -0,0 +1,763 @@
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    combat::{Outcome, Surroundings},
+    io::{Frontend, Question, View},
+    member::{Member, NamePool},
+    morale::RECRUIT_MORALE,
+    party::Party,
+    rng::GameRng,
+    terrain::Terrain,
+    weapon::{Weapon, WeaponKind},
+};
+
+/// A location's script, played the first time the party explores its tile.
+///
+/// Scenes are a list of steps run in order. Numbers can be rolled into named
+/// variables, and both variables and the party's resources can be shown in
+/// text by wrapping their name in braces, such as `+{found} food` or
+/// `You have {money} money.`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// What saves use to refer to the location. Must be unique.
+    pub id: String,
+    /// The name shown to the player.
+    pub name: String,
+    /// How often the location appears on the map, relative to the others.
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// The terrain the location can turn up on. Left empty, it can turn up
+    /// anywhere.
+    #[serde(default)]
+    pub terrain: Vec<Terrain>,
+    pub steps: Vec<Step>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Writes a full line of text.
+    Say(String),
+    /// Writes text without starting a new line.
+    Write(String),
+    Pause,
+    Ellipsis,
+    /// Waits for the player to continue.
+    Wait,
+    /// Rolls a number between `min` and `max`, inclusive, into a variable.
+    Roll {
+        name: String,
+        min: Value,
+        max: Value,
+    },
+    /// Stores a value in a variable.
+    Set {
+        name: String,
+        value: Value,
+    },
+    /// Adds to one of the party's resources, or takes away if negative.
+    Add {
+        resource: Resource,
+        amount: Value,
+    },
+    /// Runs `then` with the given probability, or `else` otherwise.
+    Chance {
+        chance: f64,
+        then: Vec<Step>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<Step>,
+    },
+    /// Runs `then` if the comparison holds, or `else` otherwise.
+    If {
+        left: Value,
+        op: Comparison,
+        right: Value,
+        then: Vec<Step>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<Step>,
+    },
+    /// Asks the player a yes or no question. The prompt is shown to the
+    /// player, and the question is given to automated players.
+    Confirm {
+        prompt: String,
+        question: String,
+        yes: Vec<Step>,
+        no: Vec<Step>,
+    },
+    /// Asks how much money to spend, storing the amount in a variable. The
+    /// money is not taken until an `add` step takes it.
+    Spend {
+        prompt: String,
+        question: String,
+        name: String,
+    },
+    /// The party fights this many zombies. The scene ends if nobody survives.
+    Combat(Value),
+    /// Survivors join the party, with their health and infection rolled from
+    /// the given ranges.
+    Recruit {
+        count: Value,
+        max_hp: [i32; 2],
+        hp: [i32; 2],
+        infection: [i32; 2],
+    },
+    /// The party finds one of these weapons, at random, and chooses who
+    /// carries it.
+    Weapon(Vec<WeaponKind>),
+}
+
+/// A whole number, the contents of a variable (negated if prefixed with
+/// `-`), or the smallest or sum of several values.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Number(i32),
+    Variable(String),
+    Min { min: Vec<Value> },
+    Sum { sum: Vec<Value> },
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = "==")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Ammo,
+    Bullets,
+    Money,
+    Fuel,
+    Food,
+    Medicine,
+    Scrap,
+}
+
+impl Resource {
+    const NAMES: &'static [&'static str] = &[
+        "ammo", "bullets", "money", "fuel", "food", "medicine", "scrap",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ammo" => Some(Resource::Ammo),
+            "bullets" => Some(Resource::Bullets),
+            "money" => Some(Resource::Money),
+            "fuel" => Some(Resource::Fuel),
+            "food" => Some(Resource::Food),
+            "scrap" => Some(Resource::Scrap),
+            "medicine" => Some(Resource::Medicine),
+            _ => None,
+        }
+    }
+
+    fn get(self, party: &Party) -> i32 {
+        match self {
+            Resource::Ammo => party.ammo,
+            Resource::Bullets => party.bullets,
+            Resource::Money => party.money,
+            Resource::Fuel => party.fuel,
+            Resource::Food => party.food,
+            Resource::Scrap => party.scrap,
+            Resource::Medicine => party.medicine,
+        }
+    }
+
+    fn of(self, party: &mut Party) -> &mut i32 {
+        match self {
+            Resource::Ammo => &mut party.ammo,
+            Resource::Bullets => &mut party.bullets,
+            Resource::Money => &mut party.money,
+            Resource::Fuel => &mut party.fuel,
+            Resource::Food => &mut party.food,
+            Resource::Scrap => &mut party.scrap,
+            Resource::Medicine => &mut party.medicine,
+        }
+    }
+}
+
+/// A problem with a step of a scene, found before the game starts.
+pub struct Invalid {
+    /// Where the step is, such as `steps[3].yes[0]`.
+    pub step: String,
+    pub reason: String,
+}
+
+impl Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` {}", self.step, self.reason)
+    }
+}
+
+/// Whether the scene should keep going after a step.
+enum Flow {
+    Continue,
+    /// The party has been wiped out, or has run from a fight.
+    End,
+}
+
+/// The variables set so far while playing a scene.
+struct State {
+    variables: HashMap<String, i32>,
+    /// Whether the party ran from a fight, cutting the scene short.
+    fled: bool,
+}
+
+impl Scene {
+    /// Plays the scene out. Returns whether the party fled from a fight,
+    /// leaving the rest of the scene for another visit.
+    pub fn play(
+        &self,
+        party: &mut Party,
+        name_pool: &mut NamePool,
+        surroundings: &mut Surroundings,
+        rng: &mut GameRng,
+        io: &mut dyn Frontend,
+    ) -> bool {
+        let mut state = State {
+            variables: HashMap::new(),
+            fled: false,
+        };
+        run(
+            &self.steps,
+            &mut state,
+            party,
+            name_pool,
+            surroundings,
+            rng,
+            io,
+        );
+        state.fled
+    }
+
+    /// Checks for mistakes that would otherwise only show up part way through
+    /// a run, such as misspelt variables and impossible ranges.
+    pub fn validate(&self) -> Result<(), Invalid> {
+        if self.id.is_empty() {
+            return Err(Invalid {
+                step: "id".to_owned(),
+                reason: "cannot be empty".to_owned(),
+            });
+        }
+        if self.weight == 0 {
+            return Err(Invalid {
+                step: "weight".to_owned(),
+                reason: "must be above 0".to_owned(),
+            });
+        }
+
+        let mut defined = HashSet::new();
+        validate_steps("steps", &self.steps, &mut defined)
+    }
+}
+
+fn run(
+    steps: &[Step],
+    state: &mut State,
+    party: &mut Party,
+    name_pool: &mut NamePool,
+    surroundings: &mut Surroundings,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Flow {
+    for step in steps {
+        let flow = match step {
+            Step::Say(text) => {
+                io.say(&state.format(text, party));
+                Flow::Continue
+            }
+            Step::Write(text) => {
+                io.write(&state.format(text, party));
+                Flow::Continue
+            }
+            Step::Pause => {
+                io.pause();
+                Flow::Continue
+            }
+            Step::Ellipsis => {
+                io.ellipsis();
+                Flow::Continue
+            }
+            Step::Wait => {
+                io.wait();
+                Flow::Continue
+            }
+            Step::Roll { name, min, max } => {
+                let (min, max) = (state.get(min), state.get(max));
+                let rolled = rng.gen_range(min..=max.max(min));
+                state.variables.insert(name.clone(), rolled);
+                Flow::Continue
+            }
+            Step::Set { name, value } => {
+                let value = state.get(value);
+                state.variables.insert(name.clone(), value);
+                Flow::Continue
+            }
+            Step::Add { resource, amount } => {
+                *resource.of(party) += state.get(amount);
+                Flow::Continue
+            }
+            Step::Chance {
+                chance,
+                then,
+                otherwise,
+            } => {
+                let steps = if rng.gen_bool(*chance) {
+                    then
+                } else {
+                    otherwise
+                };
+                run(steps, state, party, name_pool, surroundings, rng, io)
+            }
+            Step::If {
+                left,
+                op,
+                right,
+                then,
+                otherwise,
+            } => {
+                let steps = if op.holds(state.get(left), state.get(right)) {
+                    then
+                } else {
+                    otherwise
+                };
+                run(steps, state, party, name_pool, surroundings, rng, io)
+            }
+            Step::Confirm {
+                prompt,
+                question,
+                yes,
+                no,
+            } => {
+                let steps = if confirm(prompt, question, state, party, io) {
+                    yes
+                } else {
+                    no
+                };
+                run(steps, state, party, name_pool, surroundings, rng, io)
+            }
+            Step::Spend {
+                prompt,
+                question,
+                name,
+            } => {
+                let amount = spend(prompt, question, state, party, io);
+                state.variables.insert(name.clone(), amount);
+                Flow::Continue
+            }
+            Step::Combat(zombies) => {
+                match party.combat(state.get(zombies), surroundings, rng, io) {
+                    Outcome::Won if !party.check_failure() => Flow::Continue,
+                    Outcome::Fled => {
+                        state.fled = true;
+                        Flow::End
+                    }
+                    _ => Flow::End,
+                }
+            }
+            Step::Recruit {
+                count,
+                max_hp,
+                hp,
+                infection,
+            } => {
+                for _ in 0..state.get(count) {
+                    let member = Member::survivor(
+                        &name_pool.get(rng),
+                        rng.gen_range(max_hp[0]..=max_hp[1]),
+                        rng.gen_range(infection[0]..=infection[1]),
+                        rng.gen_range(hp[0]..=hp[1]),
+                        rng,
+                    );
+                    io.say(&format!("You are joined by {}", member));
+                    io.wait();
+                    party.change_morale(RECRUIT_MORALE);
+                    party.members.push(member);
+                }
+                Flow::Continue
+            }
+            Step::Weapon(kinds) => {
+                let kind = *kinds.choose(rng).expect("weapons to have been validated");
+                party.arm(Weapon::new(kind), io);
+                Flow::Continue
+            }
+        };
+
+        if let Flow::End = flow {
+            return Flow::End;
+        }
+    }
+
+    Flow::Continue
+}
+
+fn confirm(
+    prompt: &str,
+    question: &str,
+    state: &State,
+    party: &Party,
+    io: &mut dyn Frontend,
+) -> bool {
+    loop {
+        io.say(&state.format(prompt, party));
+        let input = io.ask(Question::Confirm(View::party(party), question), ": ");
+
+        match input.to_lowercase().trim() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => io.say("Invalid input."),
+        }
+    }
+}
+
+fn spend(prompt: &str, question: &str, state: &State, party: &Party, io: &mut dyn Frontend) -> i32 {
+    loop {
+        let question = Question::Spend(View::party(party), question);
+        let input = io.ask(question, &state.format(prompt, party));
+        let amount = input.trim().parse::<i32>().unwrap_or(-1);
+
+        if amount < 0 || amount > party.money {
+            io.say("Invalid amount.");
+        } else {
+            return amount;
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, left: i32, right: i32) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+impl State {
+    fn get(&self, value: &Value) -> i32 {
+        match value {
+            Value::Number(number) => *number,
+            Value::Variable(name) => match name.strip_prefix('-') {
+                Some(name) => -self.variable(name),
+                None => self.variable(name),
+            },
+            Value::Min { min: values } => values
+                .iter()
+                .map(|value| self.get(value))
+                .fold(i32::MAX, min),
+            Value::Sum { sum } => sum.iter().map(|value| self.get(value)).sum(),
+        }
+    }
+
+    fn variable(&self, name: &str) -> i32 {
+        self.variables.get(name).copied().unwrap_or(0)
+    }
+
+    /// Replaces every `{name}` in the text with the variable or resource of
+    /// that name.
+    fn format(&self, text: &str, party: &Party) -> String {
+        let mut output = String::new();
+        let mut rest = text;
+        while let Some((name, before, after)) = placeholder(rest) {
+            output.push_str(before);
+            let value = match Resource::from_name(name) {
+                Some(resource) => resource.get(party),
+                None => self.variable(name),
+            };
+            output.push_str(&value.to_string());
+            rest = after;
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Finds the first `{name}` in the text, returning the name and the text on
+/// either side of it.
+fn placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let start = text.find('{')?;
+    let end = start + text[start..].find('}')?;
+    Some((&text[start + 1..end], &text[..start], &text[end + 1..]))
+}
+
+fn validate_steps(
+    path: &str,
+    steps: &[Step],
+    defined: &mut HashSet<String>,
+) -> Result<(), Invalid> {
+    for (index, step) in steps.iter().enumerate() {
+        let path = format!("{path}[{index}]");
+        let invalid = |reason: String| {
+            Err(Invalid {
+                step: path.clone(),
+                reason,
+            })
+        };
+
+        match step {
+            Step::Say(text) | Step::Write(text) => validate_text(&path, text, defined)?,
+            Step::Pause | Step::Ellipsis | Step::Wait => {}
+            Step::Roll { name, min, max } => {
+                validate_value(&path, min, defined)?;
+                validate_value(&path, max, defined)?;
+                if let (Value::Number(min), Value::Number(max)) = (min, max) {
+                    if min > max {
+                        return invalid(format!(
+                            "rolls between {min} and {max}, which is backwards"
+                        ));
+                    }
+                }
+                define(&path, name, defined)?;
+            }
+            Step::Set { name, value } => {
+                validate_value(&path, value, defined)?;
+                define(&path, name, defined)?;
+            }
+            Step::Add { amount, .. } | Step::Combat(amount) => {
+                validate_value(&path, amount, defined)?
+            }
+            Step::Chance {
+                chance,
+                then,
+                otherwise,
+            } => {
+                if !(0.0..=1.0).contains(chance) {
+                    return invalid(format!(
+                        "has a chance of {chance}, which is not between 0 and 1"
+                    ));
+                }
+                validate_branches(&path, [("then", then), ("else", otherwise)], defined)?;
+            }
+            Step::If {
+                left,
+                right,
+                then,
+                otherwise,
+                ..
+            } => {
+                validate_value(&path, left, defined)?;
+                validate_value(&path, right, defined)?;
+                validate_branches(&path, [("then", then), ("else", otherwise)], defined)?;
+            }
+            Step::Confirm {
+                prompt, yes, no, ..
+            } => {
+                validate_text(&path, prompt, defined)?;
+                validate_branches(&path, [("yes", yes), ("no", no)], defined)?;
+            }
+            Step::Spend { prompt, name, .. } => {
+                validate_text(&path, prompt, defined)?;
+                define(&path, name, defined)?;
+            }
+            Step::Recruit {
+                count,
+                max_hp,
+                hp,
+                infection,
+            } => {
+                validate_value(&path, count, defined)?;
+                for (field, [low, high]) in
+                    [("max_hp", max_hp), ("hp", hp), ("infection", infection)]
+                {
+                    if low > high || *low < 0 {
+                        return invalid(format!(
+                            "has an invalid `{field}` range of {low} to {high}"
+                        ));
+                    }
+                }
+                if hp[0] < 1 || hp[1] > max_hp[0] {
+                    return invalid(
+                        "needs `hp` to be at least 1 and at most the lowest `max_hp`".to_owned(),
+                    );
+                }
+            }
+            Step::Weapon(kinds) => {
+                if kinds.is_empty() {
+                    return invalid("needs at least one weapon to choose from".to_owned());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates each branch on its own, as only one of them runs, then keeps only
+/// the variables set whichever branch was taken.
+fn validate_branches(
+    path: &str,
+    branches: [(&str, &[Step]); 2],
+    defined: &mut HashSet<String>,
+) -> Result<(), Invalid> {
+    let [(first, first_steps), (second, second_steps)] = branches;
+    let mut first_defined = defined.clone();
+    validate_steps(&format!("{path}.{first}"), first_steps, &mut first_defined)?;
+    let mut second_defined = defined.clone();
+    validate_steps(
+        &format!("{path}.{second}"),
+        second_steps,
+        &mut second_defined,
+    )?;
+
+    *defined = first_defined
+        .intersection(&second_defined)
+        .cloned()
+        .collect();
+    Ok(())
+}
+
+fn define(path: &str, name: &str, defined: &mut HashSet<String>) -> Result<(), Invalid> {
+    if Resource::from_name(name).is_some() || name.is_empty() || name.starts_with('-') {
+        return Err(Invalid {
+            step: path.to_owned(),
+            reason: format!(
+                "cannot use `{name}` as a variable name, or any of {}",
+                Resource::NAMES.join(", ")
+            ),
+        });
+    }
+    defined.insert(name.to_owned());
+    Ok(())
+}
+
+fn validate_value(path: &str, value: &Value, defined: &HashSet<String>) -> Result<(), Invalid> {
+    match value {
+        Value::Number(_) => Ok(()),
+        Value::Variable(name) => {
+            let name = name.strip_prefix('-').unwrap_or(name);
+            if defined.contains(name) {
+                Ok(())
+            } else {
+                Err(Invalid {
+                    step: path.to_owned(),
+                    reason: format!("uses `{name}` before it has been set"),
+                })
+            }
+        }
+        Value::Min { min: values } | Value::Sum { sum: values } => {
+            if values.is_empty() {
+                return Err(Invalid {
+                    step: path.to_owned(),
+                    reason: "has a `min` or `sum` of nothing".to_owned(),
+                });
+            }
+            values
+                .iter()
+                .try_for_each(|value| validate_value(path, value, defined))
+        }
+    }
+}
+
+fn validate_text(path: &str, text: &str, defined: &HashSet<String>) -> Result<(), Invalid> {
+    let mut rest = text;
+    while let Some((name, _, after)) = placeholder(rest) {
+        if Resource::from_name(name).is_none() && !defined.contains(name) {
+            return Err(Invalid {
+                step: path.to_owned(),
+                reason: format!(
+                    "shows `{{{name}}}`, which is neither a resource nor a variable set before it"
+                ),
+            });
+        }
+        rest = after;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, rng, tile::Tile, time::Time};
+
+    /// Validates a scene with the given steps.
+    fn validate(steps: &str) -> Result<(), Invalid> {
+        let scene: Scene =
+            serde_json::from_str(&format!(r#"{{"id":"Test","name":"Test","steps":{steps}}}"#))
+                .expect("scene to parse");
+        scene.validate()
+    }
+
+    fn rejected_at(result: Result<(), Invalid>, step: &str) -> bool {
+        matches!(result, Err(invalid) if invalid.step == step)
+    }
+
+    #[test]
+    fn variable_set_in_one_branch_is_not_set_in_the_other() {
+        let steps = r#"[{"confirm":{"prompt":"Open it?","question":"open","yes":[{"set":{"name":"found","value":1}}],"no":[{"add":{"resource":"food","amount":"found"}}]}}]"#;
+        assert!(rejected_at(validate(steps), "steps[0].no[0]"));
+    }
+
+    #[test]
+    fn variable_set_in_one_branch_is_not_set_after_it() {
+        let steps = r#"[{"chance":{"chance":0.5,"then":[{"roll":{"name":"found","min":1,"max":2}}]}},{"say":"+{found} food"}]"#;
+        assert!(rejected_at(validate(steps), "steps[1]"));
+    }
+
+    #[test]
+    fn variable_set_in_every_branch_is_set_after_them() {
+        let steps = r#"[{"if":{"left":1,"op":">","right":0,"then":[{"set":{"name":"found","value":1}}],"else":[{"set":{"name":"found","value":2}}]}},{"say":"+{found} food"}]"#;
+        assert!(validate(steps).is_ok());
+    }
+
+    #[test]
+    fn fleeing_a_fight_ends_the_scene() {
+        let scene: Scene =
+            serde_json::from_str(include_str!("../data/locations/military_base.json"))
+                .expect("scene to parse");
+        let mut fled = 0;
+        for seed in 0..40 {
+            let mut rng = rng::seeded(seed);
+            let mut party = Party::create(&mut rng, &mut Scripted::new(["1"]));
+            let ammo = party.ammo;
+            let mut tile = Tile::new(Terrain::Forest, false, None);
+            let mut surroundings = Surroundings {
+                time: &mut Time::day_zero(),
+                tile: &mut tile,
+                on_foot: false,
+            };
+            // Open the blast door, then try to run from whatever is inside.
+            let mut io = Scripted::new(["y", "1"].into_iter().chain(["5"; 200]));
+            if !scene.play(
+                &mut party,
+                &mut NamePool::new(),
+                &mut surroundings,
+                &mut rng,
+                &mut io,
+            ) {
+                continue;
+            }
+            fled += 1;
+            assert!(!io.output().contains("weapons locker"), "seed {seed}");
+            assert_eq!(party.ammo, ammo, "seed {seed}");
+        }
+        assert!(fled > 0);
+    }
+}
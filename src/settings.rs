@@ -0,0 +1,107 @@
+use std::{
+    fmt::Display,
+    fs::{create_dir_all, read_to_string, write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{io::Frontend, save::data_dir};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// How long the game lingers on dramatic pauses and ellipses.
+#[derive(Clone, Copy, PartialEq, Eq, Default, EnumIter, Serialize, Deserialize)]
+pub enum TextSpeed {
+    #[default]
+    Normal,
+    Fast,
+    Instant,
+}
+
+impl Display for TextSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TextSpeed::Normal => "Normal",
+            TextSpeed::Fast => "Fast",
+            TextSpeed::Instant => "Instant",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl TextSpeed {
+    pub fn scale(self, duration: Duration) -> Duration {
+        match self {
+            TextSpeed::Normal => duration,
+            TextSpeed::Fast => duration / 4,
+            TextSpeed::Instant => Duration::ZERO,
+        }
+    }
+}
+
+/// Preferences that apply to every run, stored outside of the save slots.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub text_speed: TextSpeed,
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults if it is missing
+    /// or unreadable.
+    pub fn load() -> Self {
+        read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            let _ = create_dir_all(dir);
+        }
+        let json = serde_json::to_string(self).expect("serialize to succeed");
+        // Failing to remember a preference is not worth interrupting the game.
+        let _ = write(path, json);
+    }
+
+    fn path() -> PathBuf {
+        data_dir().join(SETTINGS_FILE)
+    }
+}
+
+pub fn show_menu(io: &mut dyn Frontend) {
+    io.clear();
+    loop {
+        io.say(&format!("Text speed: {}\n", io.text_speed()));
+        for (num, speed) in TextSpeed::iter().enumerate() {
+            io.say(&format!("({num}) {speed}", num = num + 1));
+        }
+        io.say("Enter a number to change the text speed, or 'back' to exit this menu.");
+        let input = io.prompt(": ").trim().to_lowercase();
+
+        if input == "back" {
+            return;
+        }
+
+        let choice = input
+            .parse::<usize>()
+            .ok()
+            .and_then(|num| num.checked_sub(1))
+            .and_then(|index| TextSpeed::iter().nth(index));
+        match choice {
+            Some(speed) => {
+                io.set_text_speed(speed);
+                io.say(&format!("Text speed set to {speed}."));
+                io.wait();
+                return;
+            }
+            None => io.say("Invalid input.\n"),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write as _},
+    fs::write,
+    io,
+    path::Path,
+};
+
+use crate::{
+    content::Content,
+    game::Game,
+    io::Silent,
+    party::{Cause, Party},
+    strategy::{self, Bot, Strategy},
+};
+
+/// How many games to play, and how.
+pub struct Config {
+    pub games: usize,
+    /// The seed of the first game. Each following game uses the next seed.
+    pub seed: u64,
+    /// Games still going after this many turns are stopped and counted as
+    /// survivors, so strategies that never die cannot stall the simulation.
+    pub max_turns: usize,
+}
+
+/// The party's resources at the start of a day.
+#[derive(Clone, Copy, Default)]
+pub struct Resources {
+    pub members: i32,
+    pub ammo: i32,
+    pub money: i32,
+    pub fuel: i32,
+    pub food: i32,
+    pub medicine: i32,
+}
+
+impl Resources {
+    fn of(party: &Party) -> Self {
+        Self {
+            members: party.members.len() as i32,
+            ammo: party.ammo,
+            money: party.money,
+            fuel: party.fuel,
+            food: party.food,
+            medicine: party.medicine,
+        }
+    }
+}
+
+/// How the last member of a party died, and what killed them: whatever the
+/// party was fighting that turn, or else the cause itself, such as starvation
+/// or infection.
+pub struct Death {
+    pub cause: Cause,
+    pub killer: String,
+}
+
+pub struct GameResult {
+    pub seed: u64,
+    pub days: i32,
+    pub turns: usize,
+    /// `None` if the party was still alive when the game was stopped.
+    pub death: Option<Death>,
+    /// Whether the party won the campaign.
+    pub escaped: bool,
+    /// The resources at the start of each day survived, indexed by day.
+    pub resources: Vec<Resources>,
+}
+
+/// Plays one full game with the given strategy, without showing any output.
+pub fn play(
+    seed: u64,
+    strategy: &mut dyn Strategy,
+    max_turns: usize,
+    content: &Content,
+) -> GameResult {
+    let mut io = Silent;
+    let mut bot = Bot::new(strategy, &mut io);
+    let mut game = Game::new(seed, content, &mut bot);
+
+    let mut resources = Vec::new();
+    let mut death = None;
+    let mut turns = 0;
+    while game.alive() && !game.escaped() && turns < max_turns {
+        while resources.len() <= game.time().day() as usize {
+            resources.push(Resources::of(game.party()));
+        }
+
+        let report = game.turn(content, &mut bot);
+        turns += 1;
+
+        if !game.alive() {
+            let cause = report
+                .casualties
+                .last()
+                .map_or(Cause::Zombies, |casualty| casualty.cause);
+            let killer = match (cause, report.encounter) {
+                (Cause::Zombies | Cause::LeftBehind, Some(encounter)) => encounter.to_string(),
+                _ => cause.to_string(),
+            };
+            death = Some(Death { cause, killer });
+        }
+    }
+
+    GameResult {
+        seed,
+        days: game.time().day(),
+        turns,
+        death,
+        escaped: game.escaped(),
+        resources,
+    }
+}
+
+/// The results of many games played with the same strategy.
+pub struct Report {
+    pub strategy: String,
+    pub results: Vec<GameResult>,
+}
+
+impl Report {
+    /// Plays every game with a fresh instance of the named strategy, seeded
+    /// with the game's seed.
+    pub fn run(config: &Config, strategy: &str, content: &Content) -> Self {
+        let results = (0..config.games as u64)
+            .map(|n| {
+                let seed = config.seed.wrapping_add(n);
+                let mut player = strategy::by_name(strategy, seed).expect("strategy to exist");
+                play(seed, player.as_mut(), config.max_turns, content)
+            })
+            .collect();
+
+        Self {
+            strategy: strategy.to_owned(),
+            results,
+        }
+    }
+
+    /// Writes `games.csv`, with one row per game, and `resources.csv`, with the
+    /// average resources of the surviving parties on each day.
+    pub fn write_csv(&self, dir: &Path) -> io::Result<()> {
+        let mut games = String::from(
+            "seed,days,turns,survived,escaped,cause,killer,members,ammo,money,fuel,food,medicine\n",
+        );
+        for result in self.results.iter() {
+            let last = result.resources.last().copied().unwrap_or_default();
+            let (cause, killer) = match &result.death {
+                Some(death) => (quote(&death.cause.to_string()), quote(&death.killer)),
+                None => (String::new(), String::new()),
+            };
+            writeln!(
+                games,
+                "{},{},{},{},{},{cause},{killer},{},{},{},{},{},{}",
+                result.seed,
+                result.days,
+                result.turns,
+                result.death.is_none(),
+                result.escaped,
+                last.members,
+                last.ammo,
+                last.money,
+                last.fuel,
+                last.food,
+                last.medicine,
+            )
+            .expect("writing to a string to succeed");
+        }
+        write(dir.join("games.csv"), games)?;
+
+        let mut resources = String::from("day,alive,members,ammo,money,fuel,food,medicine\n");
+        for (day, alive, average) in self.resource_curve() {
+            writeln!(
+                resources,
+                "{day},{alive},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}",
+                average[0], average[1], average[2], average[3], average[4], average[5],
+            )
+            .expect("writing to a string to succeed");
+        }
+        write(dir.join("resources.csv"), resources)
+    }
+
+    /// The survival day of every game, sorted.
+    fn sorted_days(&self) -> Vec<i32> {
+        let mut days: Vec<i32> = self.results.iter().map(|result| result.days).collect();
+        days.sort_unstable();
+        days
+    }
+
+    /// For each day, how many parties were alive and their average members,
+    /// ammo, money, fuel, food and medicine.
+    fn resource_curve(&self) -> Vec<(usize, usize, [f64; 6])> {
+        let days = self
+            .results
+            .iter()
+            .map(|result| result.resources.len())
+            .max()
+            .unwrap_or(0);
+
+        (0..days)
+            .map(|day| {
+                let alive: Vec<&Resources> = self
+                    .results
+                    .iter()
+                    .filter_map(|result| result.resources.get(day))
+                    .collect();
+                let mut average = [0.0; 6];
+                for resources in alive.iter() {
+                    let values = [
+                        resources.members,
+                        resources.ammo,
+                        resources.money,
+                        resources.fuel,
+                        resources.food,
+                        resources.medicine,
+                    ];
+                    for (total, value) in average.iter_mut().zip(values) {
+                        *total += value as f64 / alive.len() as f64;
+                    }
+                }
+                (day, alive.len(), average)
+            })
+            .collect()
+    }
+}
+
+/// Counts how often each key appears, most common first.
+fn tally<K: Display>(keys: impl Iterator<Item = K>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for key in keys {
+        *counts.entry(key.to_string()).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Quotes a CSV field, as event and location names are free to contain
+/// commas and quotes.
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+fn percentile(sorted: &[i32], percent: usize) -> i32 {
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const BAR_WIDTH: usize = 40;
+
+        let games = self.results.len();
+        writeln!(
+            f,
+            "{games} games played by the `{}` strategy.\n",
+            self.strategy
+        )?;
+        if games == 0 {
+            return Ok(());
+        }
+
+        let days = self.sorted_days();
+        let survivors = self
+            .results
+            .iter()
+            .filter(|result| result.death.is_none() && !result.escaped)
+            .count();
+        let escaped = self.results.iter().filter(|result| result.escaped).count();
+        let mean = days.iter().map(|&day| day as f64).sum::<f64>() / games as f64;
+        writeln!(f, "Days survived:")?;
+        writeln!(
+            f,
+            "  mean {mean:.1}, median {}, 10th percentile {}, 90th percentile {}, max {}",
+            percentile(&days, 50),
+            percentile(&days, 10),
+            percentile(&days, 90),
+            percentile(&days, 100),
+        )?;
+        writeln!(f, "  {escaped} parties escaped, winning the campaign.")?;
+        writeln!(f, "  {survivors} parties were still alive when stopped.\n")?;
+
+        let max_day = percentile(&days, 100);
+        let width = (max_day / 10 + 1).max(1);
+        let mut buckets = vec![0; (max_day / width + 1) as usize];
+        for day in days.iter() {
+            buckets[(day / width) as usize] += 1;
+        }
+        let tallest = buckets.iter().copied().max().unwrap_or(1);
+        writeln!(f, "{:>11} | {:>6} |", "Day", "Games")?;
+        for (bucket, count) in buckets.iter().enumerate() {
+            let start = bucket as i32 * width;
+            writeln!(
+                f,
+                "{:>11} | {count:>6} | {}",
+                format!("{start}-{}", start + width - 1),
+                "#".repeat(count * BAR_WIDTH / tallest),
+            )?;
+        }
+
+        let deaths = || {
+            self.results
+                .iter()
+                .filter_map(|result| result.death.as_ref())
+        };
+        writeln!(f, "\nCause of death:")?;
+        for (cause, count) in tally(deaths().map(|death| death.cause)) {
+            writeln!(f, "  {cause:<20} {count:>6}")?;
+        }
+        writeln!(f, "\nKilled by:")?;
+        for (killer, count) in tally(deaths().map(|death| &death.killer)) {
+            writeln!(f, "  {killer:<20} {count:>6}")?;
+        }
+
+        let curve = self.resource_curve();
+        let step = (curve.len() / 20).max(1);
+        writeln!(f, "\nAverage resources of surviving parties:")?;
+        writeln!(
+            f,
+            "{:>5} {:>6} {:>8} {:>6} {:>6} {:>6} {:>6} {:>9}",
+            "Day", "Alive", "Members", "Ammo", "Money", "Fuel", "Food", "Medicine"
+        )?;
+        for (day, alive, average) in curve.iter().step_by(step) {
+            writeln!(
+                f,
+                "{day:>5} {alive:>6} {:>8.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>9.1}",
+                average[0], average[1], average[2], average[3], average[4], average[5],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_and_escaped() {
+        assert_eq!(quote("Zombies"), "\"Zombies\"");
+        assert_eq!(
+            quote("Ambushed at \"Joe's\", Main St"),
+            "\"Ambushed at \"\"Joe's\"\", Main St\""
+        );
+    }
+}
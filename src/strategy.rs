@@ -0,0 +1,637 @@
+use std::{iter, time::Duration};
+
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    campaign::{self, Landmark},
+    combat::{Move, Style},
+    io::{Frontend, Question, View},
+    map::Direction,
+    member::{Member, INFECTION_DAMAGE_THRESHOLD},
+    party::Action,
+    rng::{self, GameRng},
+    settings::TextSpeed,
+    tile::Tile,
+    time::Time,
+    upkeep::{self, Rations},
+    vehicle::{Job, Upgrade},
+    weapon::{Weapon, FISTS_DAMAGE},
+};
+
+/// An automated player, making every decision the game asks for.
+pub trait Strategy {
+    /// Whether to play the campaign rather than survival.
+    fn campaign(&mut self) -> bool;
+
+    /// Which of the gang to play as, from 0 (Velma) to 3 (Daphne).
+    fn character(&mut self) -> usize;
+
+    fn action(&mut self, view: View) -> Action;
+
+    /// Where to travel, or `None` to close the map.
+    fn direction(&mut self, view: View) -> Option<Direction>;
+
+    /// Which member to feed, by index, or `None` to stop feeding.
+    fn feed(&mut self, view: View) -> Option<usize>;
+
+    /// Which member to cure, by index, or `None` to stop curing.
+    fn cure(&mut self, view: View) -> Option<usize>;
+
+    fn confirm(&mut self, view: View, question: &str) -> bool;
+
+    fn spend(&mut self, view: View, question: &str) -> i32;
+
+    fn combat_style(&mut self, view: View, zombies: i32) -> Style;
+
+    /// What the member at `member` does this round. Only moves the party can
+    /// afford are chosen, and members only defend someone else.
+    fn combat_move(&mut self, view: View, member: usize, zombies: i32) -> Move;
+
+    /// Which member takes a weapon the party has found, by index, or `None`
+    /// to leave it behind.
+    fn equip(&mut self, view: View, weapon: &Weapon) -> Option<usize>;
+
+    fn rations(&mut self, view: View) -> Rations;
+
+    fn watch(&mut self, view: View, shift: usize) -> Option<usize>;
+
+    /// Whether to leave the mystery machine behind and go on foot.
+    fn leave_van(&mut self, view: View) -> bool;
+
+    /// What work to do on the mystery machine, or `None` to stop.
+    fn van(&mut self, view: View) -> Option<Job>;
+}
+
+/// Looks up one of the built-in strategies by name.
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Strategy>> {
+    match name {
+        "random" => Some(Box::new(RandomStrategy::new(seed))),
+        "heuristic" => Some(Box::new(HeuristicStrategy::default())),
+        _ => None,
+    }
+}
+
+pub const NAMES: &[&str] = &["random", "heuristic"];
+
+/// Plays the game by answering every question with a [`Strategy`], showing
+/// the game through another frontend as it goes.
+pub struct Bot<'a> {
+    strategy: &'a mut dyn Strategy,
+    inner: &'a mut dyn Frontend,
+    /// Who to defend, once the game asks after a defend move.
+    defending: Option<usize>,
+}
+
+impl<'a> Bot<'a> {
+    pub fn new(strategy: &'a mut dyn Strategy, inner: &'a mut dyn Frontend) -> Self {
+        Self {
+            strategy,
+            inner,
+            defending: None,
+        }
+    }
+
+    /// Turns the strategy's decision into the line a player would have typed.
+    fn answer(&mut self, question: Question) -> String {
+        let number = |index: Option<usize>| match index {
+            Some(index) => (index + 1).to_string(),
+            None => "back".to_owned(),
+        };
+
+        match question {
+            Question::Mode => if self.strategy.campaign() { "1" } else { "2" }.to_owned(),
+            Question::Character => number(Some(self.strategy.character())),
+            Question::Action(view) => self.strategy.action(view).input(),
+            Question::Direction(view) => match self.strategy.direction(view) {
+                Some(direction) => direction.input().to_owned(),
+                None => "back".to_owned(),
+            },
+            Question::Feed(view) => number(self.strategy.feed(view)),
+            Question::Cure(view) => number(self.strategy.cure(view)),
+            Question::Confirm(view, question) => {
+                let yes = self.strategy.confirm(view, question);
+                if yes { "y" } else { "n" }.to_owned()
+            }
+            Question::Spend(view, question) => self.strategy.spend(view, question).to_string(),
+            Question::CombatStyle(view, zombies) => match self.strategy.combat_style(view, zombies)
+            {
+                Style::Tactical => "1".to_owned(),
+                Style::AutoResolve => "2".to_owned(),
+            },
+            Question::CombatMove(view, member, zombies) => {
+                let choice = self.strategy.combat_move(view, member, zombies);
+                if let Move::Defend(target) = choice {
+                    self.defending = Some(target);
+                }
+                choice.input()
+            }
+            Question::Defend(_, _) => number(self.defending.take()),
+            Question::Rations(view) => self.strategy.rations(view).input(),
+            Question::Watch(view, shift) => match self.strategy.watch(view, shift) {
+                Some(index) => (index + 1).to_string(),
+                None => "none".to_owned(),
+            },
+            Question::Equip(view, weapon) => match self.strategy.equip(view, weapon) {
+                Some(index) => (index + 1).to_string(),
+                None => "leave".to_owned(),
+            },
+            Question::LeaveVan(view) => {
+                let yes = self.strategy.leave_van(view);
+                if yes { "y" } else { "n" }.to_owned()
+            }
+            Question::Van(view) => match self.strategy.van(view) {
+                Some(job) => job.input(),
+                None => "back".to_owned(),
+            },
+        }
+    }
+}
+
+impl Frontend for Bot<'_> {
+    fn write(&mut self, text: &str) {
+        self.inner.write(text);
+    }
+
+    fn read_line(&mut self) -> String {
+        panic!("automated players can only answer questions");
+    }
+
+    fn ask(&mut self, question: Question, text: &str) -> String {
+        let answer = self.answer(question);
+        self.inner.write(text);
+        self.inner.say(&answer);
+        answer
+    }
+
+    fn wait(&mut self) {
+        self.inner.pause();
+        self.inner.write("\n");
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.inner.sleep(duration);
+    }
+
+    fn text_speed(&self) -> TextSpeed {
+        self.inner.text_speed()
+    }
+}
+
+/// Picks uniformly between every sensible answer, as a baseline for other
+/// strategies to beat.
+pub struct RandomStrategy {
+    rng: GameRng,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: rng::seeded(seed),
+        }
+    }
+
+    fn member(&mut self, view: View) -> Option<usize> {
+        // Stopping is one of the options, so menus are always left eventually.
+        let choice = self.rng.gen_range(0..=view.party.members.len());
+        (choice < view.party.members.len()).then_some(choice)
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn campaign(&mut self) -> bool {
+        self.rng.gen_bool(0.5)
+    }
+
+    fn character(&mut self) -> usize {
+        self.rng.gen_range(0..4)
+    }
+
+    fn action(&mut self, _view: View) -> Action {
+        Action::iter()
+            .filter(|action| {
+                !matches!(
+                    action,
+                    Action::ShowMembers | Action::Autopilot | Action::Settings
+                )
+            })
+            .choose(&mut self.rng)
+            .expect("actions to be available")
+    }
+
+    fn direction(&mut self, _view: View) -> Option<Direction> {
+        let choice = self.rng.gen_range(0..=4);
+        Direction::iter().nth(choice)
+    }
+
+    fn feed(&mut self, view: View) -> Option<usize> {
+        self.member(view)
+    }
+
+    fn cure(&mut self, view: View) -> Option<usize> {
+        self.member(view)
+    }
+
+    fn confirm(&mut self, _view: View, _question: &str) -> bool {
+        self.rng.gen_bool(0.5)
+    }
+
+    fn spend(&mut self, view: View, _question: &str) -> i32 {
+        self.rng.gen_range(0..=view.party.money)
+    }
+
+    fn combat_style(&mut self, _view: View, _zombies: i32) -> Style {
+        if self.rng.gen_bool(0.5) {
+            Style::Tactical
+        } else {
+            Style::AutoResolve
+        }
+    }
+
+    fn combat_move(&mut self, view: View, member: usize, _zombies: i32) -> Move {
+        let party = view.party;
+        let mut moves = vec![Move::Melee, Move::Flee];
+        if party.loaded(&party.members[member]) {
+            moves.push(Move::Shoot);
+        }
+        if party.medicine > 0 {
+            moves.push(Move::Medicine);
+        }
+        if let Some(target) = (0..party.members.len())
+            .filter(|&target| target != member)
+            .choose(&mut self.rng)
+        {
+            moves.push(Move::Defend(target));
+        }
+        *moves.choose(&mut self.rng).expect("moves to be non-empty")
+    }
+
+    fn equip(&mut self, view: View, _weapon: &Weapon) -> Option<usize> {
+        (0..view.party.members.len())
+            .choose(&mut self.rng)
+            .filter(|_| self.rng.gen_bool(0.5))
+    }
+
+    fn rations(&mut self, _view: View) -> Rations {
+        Rations::iter()
+            .choose(&mut self.rng)
+            .expect("rations to be available")
+    }
+
+    fn watch(&mut self, view: View, _shift: usize) -> Option<usize> {
+        (0..view.party.members.len())
+            .choose(&mut self.rng)
+            .filter(|_| self.rng.gen_bool(0.5))
+    }
+
+    fn leave_van(&mut self, _view: View) -> bool {
+        self.rng.gen_bool(0.5)
+    }
+
+    fn van(&mut self, _view: View) -> Option<Job> {
+        let jobs: Vec<Job> = iter::once(Job::Repair)
+            .chain(Upgrade::iter().map(Job::Fit))
+            .collect();
+        jobs.choose(&mut self.rng)
+            .copied()
+            .filter(|_| self.rng.gen_bool(0.5))
+    }
+}
+
+/// Keeps the party fed and cured, and drives towards points of interest it
+/// has enough fuel to reach. Used by the in-game autopilot.
+#[derive(Default)]
+pub struct HeuristicStrategy {
+    /// Whether the party left the mystery machine since the last action, so
+    /// it does not turn straight back to it on the same trip.
+    left_van: bool,
+}
+
+impl HeuristicStrategy {
+    /// How far below the infection damage threshold members are given
+    /// medicine, as a dose cures between 5 and 10.
+    const CURE_MARGIN: i32 = 8;
+    /// How much health members need to be missing before they are fed, as a
+    /// meal heals between 2 and 4.
+    const FEED_MARGIN: i32 = 4;
+    /// The least ammo the party needs before it goes looking for a fight.
+    const FIGHT_AMMO: i32 = 10;
+    /// The snack machine never gives out more than five items, so anything
+    /// above that is saved for the next trade well.
+    const SPEND_LIMIT: i32 = 5;
+    /// The health at which a member is shielded in a fight, as a couple of
+    /// zombie hits could finish them off.
+    const DEFEND_HP: i32 = 4;
+    /// The days of food below which the party switches to meagre rations.
+    const FILLING_DAYS: i32 = 2;
+    /// The fuel a party on foot heads back to the mystery machine with.
+    const RETURN_FUEL: i32 = 6;
+    /// The longest the party will walk to reach somewhere, as every step on
+    /// foot risks an ambush.
+    const WALK_HOURS: i32 = 24;
+    /// The order upgrades are fitted in, as scrap allows.
+    const UPGRADES: &[Upgrade] = &[
+        Upgrade::RamBar,
+        Upgrade::ArmourPlating,
+        Upgrade::BigTank,
+        Upgrade::RoofRack,
+    ];
+
+    fn needs_cure(member: &Member) -> bool {
+        member.infection_level >= INFECTION_DAMAGE_THRESHOLD - Self::CURE_MARGIN
+    }
+
+    fn needs_food(member: &Member) -> bool {
+        member.hp <= member.max_hp - Self::FEED_MARGIN
+    }
+
+    /// The neediest member who passes `needs`, ranked by `urgency`.
+    fn neediest(
+        members: &[Member],
+        needs: fn(&Member) -> bool,
+        urgency: fn(&Member) -> i32,
+    ) -> Option<usize> {
+        members
+            .iter()
+            .enumerate()
+            .filter(|(_, member)| needs(member))
+            .max_by_key(|(_, member)| urgency(member))
+            .map(|(index, _)| index)
+    }
+
+    /// The most hurt member who needs feeding, if there is food for them.
+    fn feed_target(view: View) -> Option<usize> {
+        let index = Self::neediest(&view.party.members, Self::needs_food, |member| {
+            member.max_hp - member.hp
+        })?;
+        (view.party.members[index].meal() <= view.party.food).then_some(index)
+    }
+
+    /// Eats well while there are a couple of days of food left, and stretches
+    /// it out otherwise.
+    fn rations_for(view: View) -> Rations {
+        match upkeep::days_of_food(view.party, Rations::Filling) {
+            Some(days) if days < Self::FILLING_DAYS => Rations::Meagre,
+            _ => Rations::Filling,
+        }
+    }
+
+    /// Whether a tile is worth exploring: a point of interest, one of the
+    /// gang's hideouts, or the evacuation point once nobody is missing.
+    fn worth_exploring(tile: &Tile, gang_found: bool) -> bool {
+        let landmark = match &tile.landmark {
+            Some(Landmark::Hideout(_)) => true,
+            Some(Landmark::Evacuation) => gang_found,
+            None => false,
+        };
+        !tile.explored && (tile.location_type.is_some() || landmark)
+    }
+
+    /// Whether the party is parked at a tile worth exploring.
+    fn at_point_of_interest(view: View) -> bool {
+        view.map.is_some_and(|map| {
+            let gang_found = campaign::missing(map).is_empty();
+            Self::worth_exploring(map.get_tile(None), gang_found)
+        })
+    }
+
+    /// The way to the nearest tile worth exploring, heading for the gang and
+    /// the way out first, if the party has the fuel to drive there or a full
+    /// tank.
+    fn drive(view: View) -> Option<Direction> {
+        let (party, map) = (view.party, view.map?);
+        if map.on_foot() || party.van.broken_down() {
+            return None;
+        }
+        let gang_found = campaign::missing(map).is_empty();
+        let (direction, fuel) = map
+            .route(&party.van, |tile| {
+                tile.seen && tile.landmark.is_some() && Self::worth_exploring(tile, gang_found)
+            })
+            .or_else(|| {
+                map.route(&party.van, |tile| {
+                    tile.seen && Self::worth_exploring(tile, gang_found)
+                })
+            })?;
+        // Somewhere further than a full tank reaches is worth setting off for
+        // anyway, filling up along the way.
+        (fuel <= party.fuel || party.fuel == party.van.tank()).then_some(direction)
+    }
+
+    /// Repairs the mystery machine whenever it is damaged, and otherwise
+    /// fits the next upgrade once there is the scrap for it.
+    fn van_job(view: View) -> Option<Job> {
+        let (party, map) = (view.party, view.map?);
+        if map.on_foot() {
+            None
+        } else if !party.van.repaired() && party.scrap > 0 {
+            Some(Job::Repair)
+        } else {
+            Self::UPGRADES
+                .iter()
+                .find(|upgrade| !party.van.has(**upgrade))
+                .filter(|upgrade| upgrade.cost() <= party.scrap)
+                .map(|upgrade| Job::Fit(*upgrade))
+        }
+    }
+
+    /// Where to go next: driving while there is the fuel, and otherwise
+    /// walking somewhere close by, or back to the mystery machine once there
+    /// is fuel for it, unless the party only just left it.
+    fn route(view: View, left_van: bool) -> Option<Direction> {
+        let map = view.map?;
+        if let Some(direction) = Self::drive(view) {
+            return Some(direction);
+        }
+        if map.on_foot() && !left_van && view.party.fuel >= Self::RETURN_FUEL {
+            return map.van_route().map(|(direction, _)| direction);
+        }
+
+        let gang_found = campaign::missing(map).is_empty();
+        let walk = |target: &dyn Fn(&Tile) -> bool| {
+            map.walking_route(target)
+                .filter(|(_, hours)| *hours <= Self::WALK_HOURS)
+        };
+        walk(&|tile| {
+            tile.seen && tile.landmark.is_some() && Self::worth_exploring(tile, gang_found)
+        })
+        .or_else(|| walk(&|tile| tile.seen && Self::worth_exploring(tile, gang_found)))
+        .map(|(direction, _)| direction)
+    }
+}
+
+impl Strategy for HeuristicStrategy {
+    fn campaign(&mut self) -> bool {
+        true
+    }
+
+    /// Fred, who has the most health.
+    fn character(&mut self) -> usize {
+        2
+    }
+
+    fn action(&mut self, view: View) -> Action {
+        self.left_van = false;
+        let party = view.party;
+        if party.medicine > 0 && party.members.iter().any(Self::needs_cure) {
+            Action::Cure
+        } else if Self::feed_target(view).is_some() {
+            Action::Feed
+        } else if party.rations != Self::rations_for(view) {
+            Action::Rations
+        } else if Self::van_job(view).is_some() {
+            Action::Van
+        } else if view.time.is_some_and(Time::night) {
+            Action::Camp
+        } else if !Self::at_point_of_interest(view) && Self::route(view, false).is_some() {
+            Action::ShowMap
+        } else {
+            Action::Explore
+        }
+    }
+
+    fn direction(&mut self, view: View) -> Option<Direction> {
+        if Self::at_point_of_interest(view) {
+            None
+        } else {
+            Self::route(view, self.left_van)
+        }
+    }
+
+    fn feed(&mut self, view: View) -> Option<usize> {
+        Self::feed_target(view)
+    }
+
+    fn cure(&mut self, view: View) -> Option<usize> {
+        if view.party.medicine == 0 {
+            return None;
+        }
+        Self::neediest(&view.party.members, Self::needs_cure, |member| {
+            member.infection_level
+        })
+    }
+
+    fn confirm(&mut self, view: View, _question: &str) -> bool {
+        view.party.ammo >= Self::FIGHT_AMMO
+    }
+
+    fn spend(&mut self, view: View, _question: &str) -> i32 {
+        view.party.money.min(Self::SPEND_LIMIT)
+    }
+
+    fn combat_style(&mut self, _view: View, _zombies: i32) -> Style {
+        Style::Tactical
+    }
+
+    /// Shoots while there is ammo. Without it, the healthiest member shields
+    /// whoever is closest to dying, and the rest fight or run if outnumbered.
+    fn combat_move(&mut self, view: View, member: usize, zombies: i32) -> Move {
+        let party = view.party;
+        if party.loaded(&party.members[member]) {
+            return Move::Shoot;
+        }
+
+        let weakest = Self::neediest(&party.members, |_| true, |member| -member.hp);
+        let healthiest = Self::neediest(&party.members, |_| true, |member| member.hp);
+        if let (Some(weakest), Some(healthiest)) = (weakest, healthiest) {
+            let threatened = party.members[weakest].hp <= Self::DEFEND_HP;
+            if threatened && member == healthiest && weakest != member {
+                return Move::Defend(weakest);
+            }
+        }
+
+        if zombies > 2 * party.members.len() as i32 {
+            Move::Flee
+        } else {
+            Move::Melee
+        }
+    }
+
+    /// Hands the weapon to whoever is carrying the weakest one, if it is an
+    /// improvement.
+    fn equip(&mut self, view: View, weapon: &Weapon) -> Option<usize> {
+        let damage = |member: &Member| {
+            member
+                .weapon
+                .as_ref()
+                .map_or(FISTS_DAMAGE, |weapon| weapon.stats().damage)
+        };
+        let (index, worst) = view
+            .party
+            .members
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, member)| damage(member))?;
+        (weapon.stats().damage > damage(worst)).then_some(index)
+    }
+
+    fn rations(&mut self, view: View) -> Rations {
+        Self::rations_for(view)
+    }
+
+    /// The healthiest member keeps every watch, so the rest can recover.
+    fn watch(&mut self, view: View, _shift: usize) -> Option<usize> {
+        if view.party.members.len() < 2 {
+            return None;
+        }
+        view.party
+            .members
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, member)| member.hp)
+            .map(|(index, _)| index)
+    }
+
+    /// Only when there is not the fuel to drive anywhere worth going.
+    fn leave_van(&mut self, view: View) -> bool {
+        self.left_van = Self::drive(view).is_none();
+        self.left_van
+    }
+
+    fn van(&mut self, view: View) -> Option<Job> {
+        Self::van_job(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, location::Locations, map::Map, mapgen::MapParams, party::Party};
+
+    #[test]
+    fn heuristic_does_not_head_back_to_the_van_it_just_left() {
+        let mut rng = rng::seeded(1);
+        let mut party = Party::create(&mut rng, &mut Scripted::new(["3"]));
+        let mut map = Map::new(
+            &MapParams::default(),
+            &Locations::built_in(),
+            party.sight(),
+            &mut rng,
+        );
+        let time = Time::day_zero();
+        let mut strategy = HeuristicStrategy::default();
+
+        // With no fuel, there is nowhere to drive.
+        party.fuel = 0;
+        assert!(strategy.leave_van(View::new(&party, &map, &time)));
+        map.leave_van();
+        map.travel(Direction::North, party.sight());
+
+        party.fuel = HeuristicStrategy::RETURN_FUEL;
+        let view = View::new(&party, &map, &time);
+        let back = map.van_route().map(|(direction, _)| direction);
+        assert!(back.is_some());
+        assert!(strategy.direction(view) != back);
+
+        // On the next turn, it is worth going back for the van.
+        strategy.action(view);
+        assert!(strategy.direction(view) == back);
+    }
+}
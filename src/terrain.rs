@@ -0,0 +1,76 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// What a tile of the map is like, which decides what turns up there and how
+/// long it takes to get through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Terrain {
+    /// Dense streets at the heart of a town, crawling with the dead.
+    City,
+    /// The houses around a town.
+    Suburbs,
+    Forest,
+    /// Open fields, and what the rest of the map is made of.
+    #[default]
+    Farmland,
+    River,
+    /// A major road between towns. Highways cross rivers on bridges.
+    Highway,
+}
+
+impl Terrain {
+    pub const ALL: &'static [Terrain] = &[
+        Terrain::City,
+        Terrain::Suburbs,
+        Terrain::Forest,
+        Terrain::Farmland,
+        Terrain::River,
+        Terrain::Highway,
+    ];
+
+    /// How the terrain is drawn on the map before it has been explored.
+    pub fn symbol(self) -> char {
+        match self {
+            Terrain::City => '=',
+            Terrain::Suburbs => ':',
+            Terrain::Forest => '^',
+            Terrain::Farmland => '.',
+            Terrain::River => '~',
+            Terrain::Highway => '+',
+        }
+    }
+
+    /// How many hours it takes to drive into a tile of this terrain off-road.
+    pub fn travel_time(self) -> i32 {
+        match self {
+            Terrain::Highway => 4,
+            Terrain::Farmland | Terrain::Suburbs => 6,
+            Terrain::City | Terrain::River => 8,
+            Terrain::Forest => 9,
+        }
+    }
+
+    /// How much fuel it takes to drive into a tile of this terrain off-road.
+    pub fn fuel_cost(self) -> i32 {
+        match self {
+            Terrain::Highway => 1,
+            Terrain::Farmland | Terrain::Suburbs | Terrain::City | Terrain::River => 2,
+            Terrain::Forest => 3,
+        }
+    }
+}
+
+impl Display for Terrain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Terrain::City => "City",
+            Terrain::Suburbs => "Suburbs",
+            Terrain::Forest => "Forest",
+            Terrain::Farmland => "Farmland",
+            Terrain::River => "River",
+            Terrain::Highway => "Highway",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -1,109 +1,75 @@
 use std::fmt::Display;
 
-use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 
-use crate::{event::Event, location::Location, time::Time};
+use crate::{campaign::Landmark, location::Location, terrain::Terrain};
+
+/// How long it takes to drive along a highway, and along any other road.
+const HIGHWAY_TIME: i32 = 3;
+const ROAD_TIME: i32 = 4;
+/// How much fuel it takes to drive along a road.
+const ROAD_FUEL: i32 = 1;
+/// How many times longer it takes to walk somewhere than to drive there
+/// off-road.
+const WALK_FACTOR: i32 = 2;
 
 #[derive(Serialize, Deserialize)]
 pub struct Tile {
     pub seen: bool,
     pub explored: bool,
     pub location_type: Option<Location>,
+    #[serde(default)]
+    pub terrain: Terrain,
+    /// Whether a road runs through here. A road over a river is a bridge.
+    #[serde(default)]
+    pub road: bool,
+    /// How many zombies were left roaming here after the party fled.
+    #[serde(default)]
+    pub horde: i32,
+    /// Part of the campaign, if the run has one.
+    #[serde(default)]
+    pub landmark: Option<Landmark>,
 }
 
 impl Tile {
-    pub fn random() -> Self {
-        let loc_type = if thread_rng().gen_bool(0.15) {
-            Some(Location::random())
-        } else {
-            None
-        };
-
+    pub fn new(terrain: Terrain, road: bool, location_type: Option<Location>) -> Self {
         Self {
             explored: false,
             seen: false,
-            location_type: loc_type,
+            location_type,
+            terrain,
+            road,
+            horde: 0,
+            landmark: None,
         }
     }
 
-    pub fn get_event_options(&self, time: &Time) -> &[Event] {
-        if let Some(location) = &self.location_type {
-            if !time.night() {
-                match location {
-                    Location::ShoppingCentre => &[
-                        Event::Food(2),
-                        Event::Food(2),
-                        Event::Zombie(4),
-                        Event::Zombie(4),
-                        Event::Fuel(1),
-                        Event::Money(6),
-                        Event::Survivor(1),
-                    ],
-                    Location::TradeWell => &[
-                        Event::Zombie(5),
-                        Event::Zombie(5),
-                        Event::Money(3),
-                        Event::Ammo(4),
-                        Event::Fuel(1),
-                        Event::Survivor(1),
-                    ],
-                    Location::MilitaryBase => &[
-                        Event::Zombie(5),
-                        Event::Zombie(7),
-                        Event::Ammo(5),
-                        Event::Fuel(2),
-                        Event::Money(4),
-                        Event::Survivor(2),
-                    ],
-                }
-            } else {
-                match location {
-                    Location::ShoppingCentre => &[
-                        Event::Nothing,
-                        Event::Nothing,
-                        Event::Zombie(6),
-                        Event::Zombie(6),
-                        Event::Fuel(1),
-                    ],
-                    Location::TradeWell => &[
-                        Event::Nothing,
-                        Event::Nothing,
-                        Event::Zombie(5),
-                        Event::Zombie(5),
-                        Event::Ammo(4),
-                        Event::Fuel(1),
-                    ],
-                    Location::MilitaryBase => &[
-                        Event::Nothing,
-                        Event::Nothing,
-                        Event::Zombie(5),
-                        Event::Zombie(7),
-                        Event::Ammo(5),
-                        Event::Fuel(2),
-                    ],
-                }
-            }
-        } else if !time.night() {
-            &[
-                Event::Zombie(4),
-                Event::Zombie(4),
-                Event::Zombie(4),
-                Event::Food(3),
-                Event::Food(3),
-                Event::Money(3),
-                Event::Money(3),
-                Event::Ammo(2),
-                Event::Fuel(2),
-                Event::Survivor(2),
-            ]
+    /// Whether the mystery machine can get here. Rivers can only be crossed
+    /// by bridge.
+    pub fn drivable(&self) -> bool {
+        self.road || self.terrain != Terrain::River
+    }
+
+    /// How many hours it takes to drive here.
+    pub fn travel_time(&self) -> i32 {
+        match (self.road, self.terrain) {
+            (true, Terrain::Highway) => HIGHWAY_TIME,
+            (true, _) => ROAD_TIME,
+            (false, terrain) => terrain.travel_time(),
+        }
+    }
+
+    /// How many hours it takes to walk here. Rivers can be waded across.
+    pub fn walk_time(&self) -> i32 {
+        self.terrain.travel_time() * WALK_FACTOR
+    }
+
+    /// How much fuel it takes to drive here.
+    pub fn fuel_cost(&self) -> i32 {
+        if self.road {
+            ROAD_FUEL
         } else {
-            &[
-                Event::Zombie(5),
-                Event::Zombie(5),
-                Event::Nothing,
-                Event::Fuel(1),
-            ]
+            self.terrain.fuel_cost()
         }
     }
 }
@@ -112,11 +78,20 @@ impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let char;
         if self.seen {
-            if self.location_type.is_none() {
+            if self.horde > 0 {
+                char = '!';
+            } else if let (Some(landmark), false) = (&self.landmark, self.explored) {
+                char = match landmark {
+                    Landmark::Hideout(_) => 'G',
+                    Landmark::Evacuation => 'E',
+                };
+            } else if self.location_type.is_none() {
                 if self.explored {
                     char = '#';
+                } else if self.road {
+                    char = '+';
                 } else {
-                    char = '.';
+                    char = self.terrain.symbol();
                 }
             } else if self.explored {
                 char = 'X';
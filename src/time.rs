@@ -2,10 +2,21 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+/// The hours at which the party stops for breakfast, lunch and dinner.
+const MEAL_HOURS: &[i32] = &[8, 13, 18];
+/// The hour the night ends.
+const DAWN: i32 = 7;
+/// The hour the night starts.
+const DUSK: i32 = 21;
+
 #[derive(Serialize, Deserialize)]
 pub struct Time {
     hour: i32,
     day: i32,
+    /// Meal times passed since the last upkeep. Upkeep runs at the end of
+    /// every turn, before the game is saved.
+    #[serde(skip)]
+    meals: i32,
 }
 
 impl Display for Time {
@@ -22,18 +33,51 @@ impl Display for Time {
 
 impl Time {
     pub fn day_zero() -> Self {
-        Self { hour: 10, day: 0 }
+        Self {
+            hour: 10,
+            day: 0,
+            meals: 0,
+        }
     }
 
+    /// Moves the clock forward by `time` hours, noting every meal time passed
+    /// for the daily upkeep.
     pub fn advance(&mut self, time: i32) {
-        self.hour += time;
-        if self.hour > 23 {
-            self.day += self.hour / 24;
-            self.hour %= 24;
+        for _ in 0..time {
+            self.hour += 1;
+            if self.hour > 23 {
+                self.day += 1;
+                self.hour = 0;
+            }
+            if MEAL_HOURS.contains(&self.hour) {
+                self.meals += 1;
+            }
         }
     }
 
+    /// How many meal times have passed since this was last called.
+    pub fn take_meals(&mut self) -> i32 {
+        std::mem::take(&mut self.meals)
+    }
+
+    pub fn meals_per_day() -> i32 {
+        MEAL_HOURS.len() as i32
+    }
+
+    pub fn day(&self) -> i32 {
+        self.day
+    }
+
     pub fn night(&self) -> bool {
-        self.hour < 7 || self.hour > 20
+        self.hour < DAWN || self.hour >= DUSK
+    }
+
+    /// How many hours are left until the next morning, counting a whole day if
+    /// it is dawn already.
+    pub fn hours_until_dawn(&self) -> i32 {
+        match (DAWN - self.hour).rem_euclid(24) {
+            0 => 24,
+            hours => hours,
+        }
     }
 }
@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::GameRng;
+
+/// How much more infection a dose of medicine cures with a medic around.
+pub const MEDIC_CURE_BONUS: i32 = 3;
+/// How much further a scout can see from the mystery machine.
+pub const SCOUT_SIGHT: i32 = 2;
+/// The chance of a mechanic coaxing a trip out of the engine without using
+/// any fuel.
+pub const MECHANIC_SAVE_CHANCE: f64 = 0.25;
+/// How much extra damage a marksman deals with a gun.
+pub const MARKSMAN_DAMAGE: i32 = 2;
+/// How much food a glutton needs to eat a meal.
+pub const GLUTTON_MEAL: i32 = 2;
+/// How much easier it is to get away from a fight with a runner leading.
+pub const RUNNER_FLEE_BONUS: f64 = 0.15;
+
+/// Something a member is good, or bad, at. Most traits help the whole party
+/// as long as someone has them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Trait {
+    /// Medicine cures more infection.
+    Medic,
+    /// Reveals more of the map around the party.
+    Scout,
+    /// Travel sometimes uses no fuel.
+    Mechanic,
+    /// Hits harder with guns, so fewer shots are needed. Only helps the
+    /// marksman themselves.
+    Marksman,
+    /// Eats twice as much food as anyone else.
+    Glutton,
+    /// Makes fleeing from fights more likely to succeed.
+    Runner,
+}
+
+impl Trait {
+    /// The traits survivors can turn up with.
+    const ALL: &'static [Trait] = &[
+        Trait::Medic,
+        Trait::Scout,
+        Trait::Mechanic,
+        Trait::Marksman,
+        Trait::Glutton,
+        Trait::Runner,
+    ];
+
+    pub fn random(rng: &mut GameRng) -> Self {
+        *Self::ALL.choose(rng).expect("traits to be non-empty")
+    }
+
+    /// A trait a member could pick up through experience, if there are any
+    /// left they do not have. Nobody learns to be a glutton.
+    pub fn learn(known: &[Trait], rng: &mut GameRng) -> Option<Self> {
+        let learnable: Vec<Trait> = Self::ALL
+            .iter()
+            .copied()
+            .filter(|candidate| *candidate != Trait::Glutton && !known.contains(candidate))
+            .collect();
+        learnable.choose(rng).copied()
+    }
+}
+
+impl Display for Trait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Trait::Medic => "Medic",
+            Trait::Scout => "Scout",
+            Trait::Mechanic => "Mechanic",
+            Trait::Marksman => "Marksman",
+            Trait::Glutton => "Glutton",
+            Trait::Runner => "Runner",
+        };
+        write!(f, "{name}")
+    }
+}
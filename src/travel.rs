@@ -0,0 +1,200 @@
+use rand::Rng;
+
+use crate::{
+    combat::Surroundings,
+    content::Content,
+    event::Event,
+    io::Frontend,
+    map::{Direction, Map},
+    member::NamePool,
+    party::{Encounter, Party},
+    rng::GameRng,
+    time::Time,
+    traits::{Trait, MECHANIC_SAVE_CHANCE},
+};
+
+/// The chance of zombies catching the party out in the open on each step they
+/// walk.
+const WALK_AMBUSH_CHANCE: f64 = 0.1;
+/// How much likelier an ambush is after dark.
+const NIGHT_AMBUSH_CHANCE: f64 = 0.1;
+/// How much likelier an ambush is walking into a horde.
+const HORDE_AMBUSH_CHANCE: f64 = 0.4;
+/// The chance of a zombie stumbling into the road in front of the mystery
+/// machine, by day and by night.
+const ROADKILL_CHANCE: f64 = 0.1;
+const NIGHT_ROADKILL_CHANCE: f64 = 0.2;
+/// The most damage hitting a zombie does to the mystery machine.
+const ROADKILL_DAMAGE: i32 = 2;
+/// The damage driving into a horde does to the mystery machine.
+const HORDE_DAMAGE: i32 = 3;
+
+/// Drives the mystery machine one tile, which the party must have the fuel
+/// for. Anything in the way dents the van.
+pub fn drive(
+    party: &mut Party,
+    direction: Direction,
+    time: &mut Time,
+    map: &mut Map,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) {
+    let tile = map.neighbour(direction);
+    let (hours, fuel) = (tile.travel_time(), party.van.fuel_cost(tile.fuel_cost()));
+    map.travel(direction, party.sight());
+    time.advance(hours);
+
+    io.write(&format!(
+        "The party packs into the mystery machine, and you spend the next {hours} hours travelling"
+    ));
+    io.ellipsis();
+    io.ellipsis();
+    io.say("");
+    let mechanic = party
+        .members
+        .iter()
+        .find(|member| member.has(Trait::Mechanic));
+    match mechanic {
+        Some(mechanic) if rng.gen_bool(MECHANIC_SAVE_CHANCE) => {
+            io.say(&format!(
+                "{} keeps the engine running lean, and the trip uses no fuel.",
+                mechanic.name
+            ));
+            io.wait();
+        }
+        _ => party.fuel -= fuel,
+    }
+
+    let tile = map.get_tile_mut(None);
+    let mut damage = 0;
+    if tile.horde > 0 {
+        let rammed = party.van.ram().min(tile.horde);
+        tile.horde -= rammed;
+        io.say("The mystery machine ploughs straight into the horde roaming here!");
+        if rammed > 0 {
+            io.say(&format!("The ram bar smashes through {rammed} of them."));
+        }
+        damage = party.van.damage(HORDE_DAMAGE);
+    } else if rng.gen_bool(if time.night() {
+        NIGHT_ROADKILL_CHANCE
+    } else {
+        ROADKILL_CHANCE
+    }) {
+        io.say("A zombie staggers into the road right in front of the mystery machine!");
+        if party.van.ram() > 0 {
+            io.say("The ram bar throws it aside without a scratch.");
+        } else {
+            damage = party.van.damage(rng.gen_range(1..=ROADKILL_DAMAGE));
+        }
+    }
+    if damage > 0 {
+        io.say(&format!("-{damage} condition"));
+    }
+    if party.van.broken_down() {
+        io.say("Steam pours from under the bonnet, and the mystery machine shudders to a halt.");
+    }
+    if damage > 0 || party.van.broken_down() {
+        io.wait();
+    }
+}
+
+/// Walks the party one tile, which takes no fuel but much longer, and leaves
+/// them open to the dead along the way. Returns the ambush, if they were
+/// attacked.
+pub fn walk(
+    party: &mut Party,
+    direction: Direction,
+    time: &mut Time,
+    map: &mut Map,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Option<Encounter> {
+    let hours = map.neighbour(direction).walk_time();
+    map.travel(direction, party.sight());
+    time.advance(hours);
+
+    io.write(&format!(
+        "The party sets off on foot, and you spend the next {hours} hours walking"
+    ));
+    io.ellipsis();
+    io.ellipsis();
+    io.say("");
+
+    let tile = map.get_tile_mut(None);
+    let mut chance = WALK_AMBUSH_CHANCE;
+    if time.night() {
+        chance += NIGHT_AMBUSH_CHANCE;
+    }
+    if tile.horde > 0 {
+        chance += HORDE_AMBUSH_CHANCE;
+    }
+    let ambushed = rng.gen_bool(chance);
+    if ambushed {
+        let zombies = rng.gen_range(1..=3) + tile.horde;
+        tile.horde = 0;
+        io.say(&format!(
+            "Out in the open with nowhere to hide, the party is set upon by {zombies} zombies!"
+        ));
+        io.pause();
+
+        let mut surroundings = Surroundings {
+            time,
+            tile,
+            on_foot: true,
+        };
+        party.combat(zombies, &mut surroundings, rng, io);
+        party.normalise_values(false, io);
+        if party.check_failure() {
+            return Some(Encounter::Ambush);
+        }
+        party.check_infection(&mut surroundings, rng, io);
+    }
+
+    let boarded = map.board_van();
+    if boarded {
+        io.say("The mystery machine is right where you left it, and everyone piles back in.");
+        party.normalise_values(true, io);
+    }
+    if ambushed || boarded {
+        io.wait();
+    }
+    ambushed.then_some(Encounter::Ambush)
+}
+
+/// Rolls for something happening on the way into the party's new tile, which
+/// is likelier after dark and depends on the terrain. Returns what happened,
+/// if anything did.
+pub fn encounter(
+    party: &mut Party,
+    name_pool: &mut NamePool,
+    content: &Content,
+    time: &mut Time,
+    map: &mut Map,
+    rng: &mut GameRng,
+    io: &mut dyn Frontend,
+) -> Option<Encounter> {
+    let on_foot = map.on_foot();
+    let tile = map.get_tile_mut(None);
+    let options = content.events.travel_options(tile.terrain, time)?;
+    let event = Event::roll(options, rng);
+    match event {
+        Event::Nothing => return None,
+        // There is nothing to break down on foot.
+        Event::Breakdown(_) if on_foot => return None,
+        _ => {}
+    }
+
+    io.say("");
+    let mut surroundings = Surroundings {
+        time,
+        tile,
+        on_foot,
+    };
+    event.handle(party, name_pool, &mut surroundings, rng, io);
+    party.normalise_values(!on_foot, io);
+    if !party.check_failure() {
+        party.check_infection(&mut surroundings, rng, io);
+    }
+    io.wait();
+    Some(Encounter::Event(event))
+}
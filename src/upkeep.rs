@@ -0,0 +1,227 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{
+    io::{Frontend, Question, View},
+    member::{DeathCheckResult, HUNGER, SATIATED},
+    morale::{MEAL_MORALE, STARVING_MORALE},
+    party::{Cause, Party},
+    relationships::FEED_RESENTMENT,
+    time::Time,
+};
+
+/// How many meals one food makes, which is a day's worth for one member.
+const PORTIONS: i32 = 3;
+/// The health lost at every meal time a member goes without food once they
+/// have nothing left in them.
+const STARVATION_DAMAGE: i32 = 1;
+/// The health a meal gives back.
+const MEAL_HEAL: i32 = 1;
+/// How empty a member has to be before they eat on meagre rations.
+const MEAGRE_HUNGER: i32 = SATIATED - 2 * HUNGER;
+
+/// How the party shares out food at meal times.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum Rations {
+    /// Everyone eats at every meal time.
+    #[default]
+    Filling,
+    /// Members only eat once they are getting hungry, which uses about half
+    /// as much food.
+    Meagre,
+    /// Nobody eats, and the food is saved.
+    Nothing,
+}
+
+impl Rations {
+    fn label(self) -> &'static str {
+        match self {
+            Rations::Filling => "Filling: everyone eats at every meal",
+            Rations::Meagre => "Meagre: members only eat when they are hungry",
+            Rations::Nothing => "Nothing: save the food",
+        }
+    }
+
+    /// The number typed to choose this policy.
+    pub fn input(self) -> String {
+        let index = Rations::iter()
+            .position(|rations| rations == self)
+            .expect("rations to be listed");
+        (index + 1).to_string()
+    }
+
+    fn from_input(input: &str) -> Option<Self> {
+        let index = input.trim().parse::<usize>().ok()?.checked_sub(1)?;
+        Rations::iter().nth(index)
+    }
+}
+
+impl Display for Rations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Rations::Filling => "filling",
+            Rations::Meagre => "meagre",
+            Rations::Nothing => "nothing",
+        };
+        write!(f, "{name}")
+    }
+}
+
+pub fn choose_rations(party: &mut Party, io: &mut dyn Frontend) {
+    io.clear();
+    io.say(&format!(
+        "The party is currently eating {} rations.",
+        party.rations
+    ));
+    loop {
+        io.say("\nHow should food be shared out at meal times?");
+        for (num, rations) in Rations::iter().enumerate() {
+            io.say(&format!("({num}) {}", rations.label(), num = num + 1));
+        }
+        let input = io.ask(Question::Rations(View::party(party)), ": ");
+
+        match Rations::from_input(&input) {
+            Some(rations) => {
+                party.rations = rations;
+                io.say(&format!("The party will now eat {rations} rations."));
+                io.wait();
+                return;
+            }
+            None => io.say("Invalid option."),
+        }
+    }
+}
+
+/// How many days the party's food will last on the given rations, or `None`
+/// if nobody is eating.
+pub fn days_of_food(party: &Party, rations: Rations) -> Option<i32> {
+    let per_meal: i32 = party.members.iter().map(|member| member.meal()).sum();
+    let per_day = per_meal * Time::meals_per_day();
+    let portions = party.food * PORTIONS + party.portions;
+    match rations {
+        _ if per_day == 0 => None,
+        Rations::Filling => Some(portions / per_day),
+        // Each member eats at every other meal time.
+        Rations::Meagre => Some(portions * 2 / per_day),
+        Rations::Nothing => None,
+    }
+}
+
+/// Everyone gets hungrier at every meal time that has passed, and eats
+/// according to the party's rations, with each food shared out over several
+/// meals. Members with nothing left in them starve.
+pub fn eat(party: &mut Party, meals: i32, io: &mut dyn Frontend) {
+    for _ in 0..meals {
+        if party.members.is_empty() {
+            return;
+        }
+
+        io.write("The party stops to eat");
+        io.ellipsis();
+        io.say("");
+        let mut eaten = 0;
+        let mut fed = Vec::new();
+        let mut unfed = Vec::new();
+        for member in party.members.iter_mut() {
+            member.satiation = (member.satiation - HUNGER).max(0);
+            let hungry = match party.rations {
+                Rations::Filling => true,
+                Rations::Meagre => member.satiation <= MEAGRE_HUNGER,
+                Rations::Nothing => false,
+            };
+            if hungry {
+                while party.portions < member.meal() && party.food > 0 {
+                    party.food -= 1;
+                    party.portions += PORTIONS;
+                    eaten += 1;
+                }
+                if party.portions >= member.meal() {
+                    party.portions -= member.meal();
+                    member.satiation = SATIATED;
+                    member.heal(MEAL_HEAL);
+                    fed.push(member.name.clone());
+                    // Only a full meal at every sitting does much for spirits.
+                    if party.rations == Rations::Filling {
+                        member.change_morale(MEAL_MORALE);
+                    }
+                } else {
+                    io.say(&format!("There is not enough food for {}.", member.name));
+                    unfed.push(member.name.clone());
+                }
+            }
+
+            if member.satiation == 0 {
+                member.hp -= STARVATION_DAMAGE.min(member.hp);
+                member.change_morale(-STARVING_MORALE);
+                io.say(&format!(
+                    "{} is starving, and takes {STARVATION_DAMAGE} damage.",
+                    member.name
+                ));
+            }
+        }
+        if eaten > 0 {
+            io.say(&format!("-{eaten} food"));
+        }
+        // Those who went without resent whoever ate ahead of them.
+        for hungry in unfed.iter() {
+            for full in fed.iter() {
+                party.relationships.change(hungry, full, -FEED_RESENTMENT);
+            }
+        }
+
+        for member in std::mem::take(&mut party.members) {
+            match member.check_dead() {
+                DeathCheckResult::Alive => party.members.push(member),
+                DeathCheckResult::Dead | DeathCheckResult::Undead => {
+                    party.bury(&member, Cause::Starvation);
+                    io.say(&format!("{} has starved to death.", member.name));
+                }
+            }
+        }
+        io.pause();
+    }
+    io.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Scripted, rng};
+
+    /// Velma on her own, with the given food.
+    fn velma(food: i32) -> Party {
+        let mut party = Party::create(&mut rng::seeded(1), &mut Scripted::new(["1"]));
+        party.members.truncate(1);
+        party.food = food;
+        party
+    }
+
+    #[test]
+    fn one_food_lasts_a_day_of_meals() {
+        let mut party = velma(1);
+        assert_eq!(days_of_food(&party, Rations::Filling), Some(1));
+
+        eat(&mut party, Time::meals_per_day(), &mut Scripted::default());
+        assert_eq!(party.food, 0);
+        assert_eq!(party.portions, 0);
+        assert_eq!(party.members[0].satiation, SATIATED);
+    }
+
+    #[test]
+    fn meagre_rations_last_twice_as_long() {
+        let mut party = velma(1);
+        assert_eq!(days_of_food(&party, Rations::Meagre), Some(2));
+
+        party.rations = Rations::Meagre;
+        eat(
+            &mut party,
+            2 * Time::meals_per_day(),
+            &mut Scripted::default(),
+        );
+        assert_eq!(party.food, 0);
+        assert!(party.members[0].satiation > 0);
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{
+    io::{Frontend, Question, View},
+    map::Map,
+    party::Party,
+    time::Time,
+};
+
+/// The best condition the mystery machine can be in.
+const MAX_CONDITION: i32 = 10;
+/// At or below this condition, the engine burns an extra unit of fuel on
+/// every tile.
+const WORN_CONDITION: i32 = 4;
+/// How much fuel the tank holds, and how much more the bigger tank holds.
+const TANK: i32 = 20;
+const BIG_TANK: i32 = 20;
+/// How much food and medicine fit in the back, and how much more the roof
+/// rack carries.
+const STORAGE: i32 = 30;
+const ROOF_RACK_STORAGE: i32 = 20;
+/// How many zombies the ram bar ploughs through before they reach the van.
+const RAM_KILLS: i32 = 2;
+/// How much easier it is to get away with armour plating keeping the dead
+/// off the doors.
+pub const ARMOUR_FLEE_BONUS: f64 = 0.1;
+/// How much scrap, and how many hours, each point of repairs takes.
+const REPAIR_SCRAP: i32 = 1;
+const REPAIR_TIME: i32 = 1;
+/// How many hours it takes to fit an upgrade.
+const FIT_TIME: i32 = 3;
+
+/// Something the party can bolt onto the mystery machine with enough scrap.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum Upgrade {
+    /// Halves the damage the van takes, and makes getting away easier.
+    ArmourPlating,
+    BigTank,
+    RoofRack,
+    /// Ploughs through zombies in the road, and on the way out of a fight.
+    RamBar,
+}
+
+impl Upgrade {
+    /// How much scrap it takes to fit.
+    pub fn cost(self) -> i32 {
+        match self {
+            Upgrade::ArmourPlating => 6,
+            Upgrade::BigTank => 4,
+            Upgrade::RoofRack => 3,
+            Upgrade::RamBar => 5,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Upgrade::ArmourPlating => "halves damage to the van, and makes fleeing easier",
+            Upgrade::BigTank => "holds twice as much fuel",
+            Upgrade::RoofRack => "carries more food and medicine",
+            Upgrade::RamBar => "ploughs through zombies in the way",
+        }
+    }
+}
+
+impl Display for Upgrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Upgrade::ArmourPlating => "Armour plating",
+            Upgrade::BigTank => "Bigger tank",
+            Upgrade::RoofRack => "Roof rack",
+            Upgrade::RamBar => "Ram bar",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Work that can be done on the mystery machine.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    /// Patches up as much damage as the party has the scrap for.
+    Repair,
+    Fit(Upgrade),
+}
+
+impl Job {
+    /// The number typed to choose this job.
+    pub fn input(self) -> String {
+        let index = match self {
+            Job::Repair => 0,
+            Job::Fit(upgrade) => {
+                1 + Upgrade::iter()
+                    .position(|candidate| candidate == upgrade)
+                    .expect("upgrade to be listed")
+            }
+        };
+        (index + 1).to_string()
+    }
+
+    fn from_input(input: &str) -> Option<Self> {
+        match input.trim().parse::<usize>().ok()? {
+            1 => Some(Job::Repair),
+            number => Upgrade::iter().nth(number.checked_sub(2)?).map(Job::Fit),
+        }
+    }
+}
+
+/// The mystery machine: how beaten up it is, and what has been bolted on.
+#[derive(Serialize, Deserialize)]
+pub struct Vehicle {
+    pub condition: i32,
+    pub upgrades: Vec<Upgrade>,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            condition: MAX_CONDITION,
+            upgrades: Vec::new(),
+        }
+    }
+}
+
+impl Display for Vehicle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = if self.broken_down() {
+            "broken down"
+        } else if self.condition <= WORN_CONDITION {
+            "worn"
+        } else {
+            "running"
+        };
+        let upgrades = if self.upgrades.is_empty() {
+            "None".to_owned()
+        } else {
+            self.upgrades
+                .iter()
+                .map(Upgrade::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "- Condition: {}/{MAX_CONDITION} ({state})\n- Tank: {} fuel\n- Storage: {} food and medicine\n- Upgrades: {upgrades}",
+            self.condition,
+            self.tank(),
+            self.storage()
+        )
+    }
+}
+
+impl Vehicle {
+    pub fn has(&self, upgrade: Upgrade) -> bool {
+        self.upgrades.contains(&upgrade)
+    }
+
+    /// The most fuel the party can carry.
+    pub fn tank(&self) -> i32 {
+        TANK + if self.has(Upgrade::BigTank) {
+            BIG_TANK
+        } else {
+            0
+        }
+    }
+
+    /// The most food and medicine the party can carry between them.
+    pub fn storage(&self) -> i32 {
+        STORAGE
+            + if self.has(Upgrade::RoofRack) {
+                ROOF_RACK_STORAGE
+            } else {
+                0
+            }
+    }
+
+    pub fn broken_down(&self) -> bool {
+        self.condition == 0
+    }
+
+    pub fn repaired(&self) -> bool {
+        self.condition == MAX_CONDITION
+    }
+
+    /// How much fuel it takes to drive somewhere that would take `fuel` in
+    /// good condition.
+    pub fn fuel_cost(&self, fuel: i32) -> i32 {
+        fuel + i32::from(self.condition <= WORN_CONDITION)
+    }
+
+    /// How many zombies the van ploughs through before they can reach it.
+    pub fn ram(&self) -> i32 {
+        if self.has(Upgrade::RamBar) {
+            RAM_KILLS
+        } else {
+            0
+        }
+    }
+
+    /// Knocks the van about, with armour plating taking half of it. Returns
+    /// how much damage got through.
+    pub fn damage(&mut self, amount: i32) -> i32 {
+        let amount = if self.has(Upgrade::ArmourPlating) {
+            amount / 2
+        } else {
+            amount
+        };
+        self.condition = (self.condition - amount).max(0);
+        amount
+    }
+}
+
+/// Lets the party repair the mystery machine and fit upgrades with the scrap
+/// they have found, as long as they are with it.
+pub fn work_on_van(party: &mut Party, map: &Map, time: &mut Time, io: &mut dyn Frontend) {
+    io.clear();
+    if map.on_foot() {
+        io.say("The mystery machine is parked back where the party left it, marked M on the map.");
+        io.wait();
+        return;
+    }
+
+    loop {
+        io.say(&format!("Mystery machine:\n{}", party.van));
+        io.say(&format!("You have {} scrap.", party.scrap));
+        io.say("\nWhat work should be done on the mystery machine?");
+        io.say(&format!(
+            "(1) Repair: {REPAIR_SCRAP} scrap and {REPAIR_TIME} hour per point of condition"
+        ));
+        for upgrade in Upgrade::iter() {
+            let fitted = if party.van.has(upgrade) {
+                " (fitted)"
+            } else {
+                ""
+            };
+            io.say(&format!(
+                "({}) {upgrade}: {}, {} scrap{fitted}",
+                Job::Fit(upgrade).input(),
+                upgrade.label(),
+                upgrade.cost()
+            ));
+        }
+
+        io.say("Enter a number, or `back` to stop working.");
+        let input = io.ask(Question::Van(View::new(party, map, time)), ": ");
+        if input.trim().to_lowercase() == "back" {
+            return;
+        }
+
+        match Job::from_input(&input) {
+            Some(Job::Repair) if party.van.repaired() => {
+                io.say("The mystery machine is already in good shape.")
+            }
+            Some(Job::Repair) if party.scrap < REPAIR_SCRAP => {
+                io.say("You do not have enough scrap to repair anything.")
+            }
+            Some(Job::Repair) => {
+                let points = (MAX_CONDITION - party.van.condition).min(party.scrap / REPAIR_SCRAP);
+                party.scrap -= points * REPAIR_SCRAP;
+                party.van.condition += points;
+                time.advance(points * REPAIR_TIME);
+                io.write(&format!(
+                    "You spend the next {} hours patching up the mystery machine",
+                    points * REPAIR_TIME
+                ));
+                io.ellipsis();
+                io.say(&format!("\n+{points} condition"));
+            }
+            Some(Job::Fit(upgrade)) if party.van.has(upgrade) => {
+                io.say(&format!("{upgrade} is already fitted."))
+            }
+            Some(Job::Fit(upgrade)) if party.scrap < upgrade.cost() => io.say(&format!(
+                "You do not have enough scrap for the {}.",
+                upgrade.to_string().to_lowercase()
+            )),
+            Some(Job::Fit(upgrade)) => {
+                party.scrap -= upgrade.cost();
+                party.van.upgrades.push(upgrade);
+                time.advance(FIT_TIME);
+                io.write(&format!(
+                    "You spend the next {FIT_TIME} hours bolting on the {}",
+                    upgrade.to_string().to_lowercase()
+                ));
+                io.ellipsis();
+                io.say("");
+            }
+            None => io.say("Invalid option."),
+        }
+        io.wait();
+        io.clear();
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// The damage a member deals with their bare hands.
+pub const FISTS_DAMAGE: i32 = 2;
+/// How much damage it takes to put a zombie down for good.
+pub const ZOMBIE_HP: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AmmoType {
+    /// Kept in `Party::ammo`, as the shotgun was the only gun before.
+    Shells,
+    /// Kept in `Party::bullets`.
+    Bullets,
+}
+
+impl Display for AmmoType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmmoType::Shells => write!(f, "shells"),
+            AmmoType::Bullets => write!(f, "bullets"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum WeaponKind {
+    Shotgun,
+    Pistol,
+    Crowbar,
+    Bat,
+    Rifle,
+}
+
+/// What a kind of weapon is capable of.
+pub struct Stats {
+    /// Damage dealt with each hit. Damage beyond what a zombie can take
+    /// carries on into the next one.
+    pub damage: i32,
+    /// What the weapon fires, or `None` for melee weapons.
+    pub ammo: Option<AmmoType>,
+    /// How many times a new weapon can be used before it breaks.
+    pub durability: i32,
+    /// The chance of each use drawing another zombie into the fight.
+    pub noise: f64,
+}
+
+impl WeaponKind {
+    pub fn stats(self) -> Stats {
+        match self {
+            WeaponKind::Shotgun => Stats {
+                damage: 6,
+                ammo: Some(AmmoType::Shells),
+                durability: 40,
+                noise: 0.3,
+            },
+            WeaponKind::Pistol => Stats {
+                damage: 4,
+                ammo: Some(AmmoType::Bullets),
+                durability: 60,
+                noise: 0.15,
+            },
+            WeaponKind::Crowbar => Stats {
+                damage: 3,
+                ammo: None,
+                durability: 40,
+                noise: 0.05,
+            },
+            WeaponKind::Bat => Stats {
+                damage: 4,
+                ammo: None,
+                durability: 15,
+                noise: 0.1,
+            },
+            WeaponKind::Rifle => Stats {
+                damage: 8,
+                ammo: Some(AmmoType::Bullets),
+                durability: 50,
+                noise: 0.25,
+            },
+        }
+    }
+}
+
+impl Display for WeaponKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WeaponKind::Shotgun => "shotgun",
+            WeaponKind::Pistol => "pistol",
+            WeaponKind::Crowbar => "crowbar",
+            WeaponKind::Bat => "bat",
+            WeaponKind::Rifle => "rifle",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A weapon carried by a party member, which wears down with use.
+#[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub durability: i32,
+}
+
+impl Display for Weapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}/{})",
+            self.kind,
+            self.durability,
+            self.kind.stats().durability
+        )
+    }
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind) -> Self {
+        Self {
+            kind,
+            durability: kind.stats().durability,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.kind.stats()
+    }
+
+    /// Wears the weapon down after a use, returning whether it has broken.
+    pub fn wear(&mut self) -> bool {
+        self.durability -= 1;
+        self.durability <= 0
+    }
+}